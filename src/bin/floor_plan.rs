@@ -0,0 +1,100 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use dark_city::entities::map::{
+    floor_plan::{render_ascii, render_svg},
+    generator::{DungeonConstraints, generate_dungeon},
+    level::parse_level,
+    map::Room,
+};
+
+const USAGE: &str = "usage: floor_plan (<level.ron> | --seed <n>) [--svg] [-o <output>]";
+
+struct Args {
+    level: Option<PathBuf>,
+    seed: Option<u64>,
+    svg: bool,
+    output: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        level: None,
+        seed: None,
+        svg: false,
+        output: None,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--svg" => args.svg = true,
+            "--ascii" => args.svg = false,
+            "--seed" => {
+                let value = iter.next().ok_or("--seed needs a value")?;
+                args.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid seed `{value}`"))?,
+                );
+            }
+            "-o" | "--output" => {
+                args.output = Some(iter.next().ok_or("-o needs a path")?.into());
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => args.level = Some(arg.into()),
+        }
+    }
+
+    if args.level.is_none() == args.seed.is_none() {
+        return Err("pass either a level file or --seed".to_string());
+    }
+    Ok(args)
+}
+
+fn load_rooms(args: &Args) -> Result<Vec<Room>, String> {
+    if let Some(seed) = args.seed {
+        return generate_dungeon(seed, &DungeonConstraints::default()).map_err(|e| e.to_string());
+    }
+
+    let path = args.level.as_ref().unwrap();
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_level(&path.display().to_string(), &bytes)
+        .map(|level| level.rooms)
+        .map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let rooms = match load_rooms(&args) {
+        Ok(rooms) => rooms,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let plan = if args.svg {
+        render_svg(&rooms)
+    } else {
+        render_ascii(&rooms)
+    };
+
+    match &args.output {
+        Some(path) => {
+            if let Err(error) = std::fs::write(path, plan) {
+                eprintln!("{}: {error}", path.display());
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{plan}"),
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,9 @@
+pub const EDITOR_FLY_SPEED: f32 = 30.0;
+pub const EDITOR_FAST_MULTIPLIER: f32 = 3.0;
+pub const EDITOR_LOOK_SENSITIVITY: f32 = 0.2;
+pub const EDITOR_MOVE_STEP: f32 = 10.0;
+pub const EDITOR_FINE_STEP: f32 = 1.0;
+pub const EDITOR_DOOR_RADIUS: f32 = 3.0;
+pub const EDITOR_MIN_WALL_HEIGHT: f32 = 2.0;
+pub const EDITOR_HISTORY_LIMIT: usize = 100;
+pub const EDITOR_AMBIENT_BRIGHTNESS: f32 = 400.0;
@@ -1 +1,6 @@
+use bevy::color::Color;
+
 pub const DEFAULT_ROOM_NAME: &str = "Entry Area";
+pub const SECRET_MESSAGE_TIME: f32 = 4.0;
+pub const STAMINA_COLOR: Color = Color::srgba(0.85, 0.75, 0.3, 0.8);
+pub const EXHAUSTED_COLOR: Color = Color::srgba(0.5, 0.5, 0.5, 0.8);
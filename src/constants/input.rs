@@ -0,0 +1,12 @@
+pub const CONTROLS_CONFIG_PATH: &str = "config/controls.ron";
+pub const GAMEPAD_MOVE_DEADZONE: f32 = 0.2;
+pub const GAMEPAD_LOOK_DEADZONE: f32 = 0.15;
+// Degrees per second at full deflection, before acceleration.
+pub const GAMEPAD_LOOK_SENSITIVITY: f32 = 150.0;
+pub const GAMEPAD_LOOK_EXPONENT: f32 = 2.0;
+pub const GAMEPAD_LOOK_BOOST: f32 = 1.8;
+pub const GAMEPAD_LOOK_BOOST_THRESHOLD: f32 = 0.9;
+pub const GAMEPAD_LOOK_BOOST_TIME: f32 = 0.5;
+pub const MENU_STICK_THRESHOLD: f32 = 0.5;
+pub const MENU_REPEAT_DELAY: f32 = 0.4;
+pub const MENU_REPEAT_INTERVAL: f32 = 0.15;
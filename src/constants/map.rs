@@ -2,3 +2,53 @@ pub const WALL_THICKNESS: f32 = 1.0;
 pub const BASE_ROOM_SIZE: f32 = 30.0;
 pub const GROUND_HEIGHT: f32 = 0.1;
 pub const ROOF_THICKNESS: f32 = 0.2;
+pub const DEFAULT_LEVEL_PATH: &str = "levels/default.level.ron";
+pub const ENTRY_ROOM_NAME: &str = "Entry Area";
+pub const ROOM_SIZE_STEP: f32 = 10.0;
+pub const GENERATED_WALL_HEIGHT: f32 = 10.0;
+pub const GENERATED_DOOR_RADIUS: f32 = 3.0;
+pub const MAX_PLACEMENT_ATTEMPTS: usize = 256;
+pub const DOOR_THICKNESS: f32 = 0.5;
+pub const DOOR_SLIDE_SPEED: f32 = 1.5;
+pub const DOOR_AUTO_OPEN_DISTANCE: f32 = 6.0;
+pub const WALL_TEXTURE_TILE: f32 = 10.0;
+pub const ARCH_SEGMENTS: usize = 12;
+pub const WALL_GEOMETRY_EPSILON: f32 = 0.001;
+pub const GROUND_TEXTURE_TILE: f32 = 12.0;
+pub const DEFAULT_THEME: &str = "default";
+pub const TORCH_HEIGHT_RATIO: f32 = 0.6;
+pub const TORCH_WALL_OFFSET: f32 = 1.0;
+pub const LIGHT_CULL_DISTANCE: f32 = 30.0;
+pub const STREAMING_RADIUS: usize = 2;
+pub const FLOOR_PLAN_ASCII_CELL: f32 = 5.0;
+pub const FLOOR_PLAN_SVG_SCALE: f32 = 3.0;
+pub const FLOOR_PLAN_SVG_MARGIN: f32 = 20.0;
+pub const PROP_WALL_INSET: f32 = 1.5;
+pub const PROP_MIN_SPACING: f32 = 2.5;
+pub const PROP_DOOR_CLEARANCE: f32 = 2.0;
+pub const MAX_SCATTER_ATTEMPTS: usize = 32;
+pub const STAIR_STEP_HEIGHT: f32 = 0.4;
+pub const STAIR_STEP_DEPTH: f32 = 1.2;
+pub const STAIR_WIDTH: f32 = 6.0;
+pub const STAIR_HEADROOM: f32 = 3.0;
+pub const STAIR_SLAB_THICKNESS: f32 = 0.3;
+pub const STAIR_WALL_MARGIN: f32 = 4.0;
+pub const RAMP_ANGLE: f32 = 25.0;
+pub const SPIKE_HEIGHT: f32 = 1.0;
+pub const SPIKE_RADIUS: f32 = 0.2;
+pub const SPIKE_SPACING: f32 = 1.5;
+pub const SPIKE_EXTEND_TIME: f32 = 0.1;
+pub const CRUSHER_THICKNESS: f32 = 1.0;
+pub const HAZARD_PLATE_THICKNESS: f32 = 0.05;
+pub const KILL_PLANE_DEPTH: f32 = 30.0;
+pub const KILL_PLANE_DAMAGE: f32 = 10.0;
+pub const PLATE_THICKNESS: f32 = 0.2;
+pub const PLATE_PRESS_DEPTH: f32 = 0.15;
+pub const LEVER_THROW: f32 = 40.0;
+pub const SWITCH_SIZE: f32 = 0.6;
+pub const PLATFORM_SPEED: f32 = 4.0;
+pub const PLATFORM_RIDE_TOLERANCE: f32 = 0.3;
+pub const SECRET_WALL_HITS: u32 = 3;
+pub const FRAGMENT_GRID: usize = 4;
+pub const FRAGMENT_SPEED: f32 = 4.0;
+pub const FRAGMENT_LIFETIME: f32 = 8.0;
@@ -1,4 +1,7 @@
+pub mod editor;
 pub mod hud;
+pub mod input;
 pub mod map;
+pub mod physics;
 pub mod player;
 pub mod text;
@@ -0,0 +1,3 @@
+pub const PHYSICS_TICK_RATE: f64 = 60.0;
+// Moves longer than this in one tick are teleports and are not smoothed.
+pub const MAX_INTERPOLATION_DISTANCE: f32 = 2.0;
@@ -1,5 +1,28 @@
+use bevy::math::Vec3;
+
 pub const MOUSE_SENSITIVITY: f32 = 0.3;
 pub const GROUND_TIMER: f32 = 0.5;
 pub const MOVEMENT_SPEED: f32 = 8.0;
 pub const JUMP_SPEED: f32 = 20.0;
 pub const GRAVITY: f32 = -9.81;
+pub const PLAYER_MAX_HEALTH: f32 = 100.0;
+pub const PLAYER_INVULNERABILITY_TIME: f32 = 1.0;
+pub const PLAYER_SPAWN_POSITION: Vec3 = Vec3::new(0.0, 5.0, 0.0);
+pub const CAMERA_OFFSET: Vec3 = Vec3::new(0.0, 0.2, -0.1);
+pub const SWORD_REACH: f32 = 3.0;
+pub const SWORD_ARC_COS: f32 = 0.6;
+pub const INTERACT_REACH: f32 = 3.0;
+pub const PLAYER_HALF_HEIGHT: f32 = 1.1;
+pub const PLAYER_RADIUS: f32 = 0.5;
+pub const PLAYER_MAX_STAMINA: f32 = 100.0;
+pub const STAMINA_REGEN_RATE: f32 = 30.0;
+pub const STAMINA_REGEN_DELAY: f32 = 0.8;
+pub const EXHAUSTED_REGEN_DELAY: f32 = 1.5;
+pub const STAMINA_RECOVERY_FRACTION: f32 = 0.4;
+pub const EXHAUSTED_SPEED_MULTIPLIER: f32 = 0.6;
+pub const SPRINT_STAMINA_PER_SECOND: f32 = 20.0;
+pub const JUMP_STAMINA: f32 = 12.0;
+pub const DODGE_STAMINA: f32 = 25.0;
+pub const SLASH_STAMINA: f32 = 18.0;
+pub const DODGE_SPEED: f32 = 18.0;
+pub const DODGE_DURATION: f32 = 0.3;
@@ -0,0 +1,77 @@
+use bevy::{input::mouse::MouseMotion, prelude::*};
+
+use crate::constants::editor::{EDITOR_FAST_MULTIPLIER, EDITOR_FLY_SPEED, EDITOR_LOOK_SENSITIVITY};
+
+#[derive(Component, Default)]
+pub struct EditorCamera {
+    yaw: f32,
+    pitch: f32,
+}
+
+pub fn spawn_editor_camera(commands: &mut Commands) {
+    let pitch = -35.0_f32;
+    commands.spawn((
+        Camera3d::default(),
+        EditorCamera { yaw: 0.0, pitch },
+        Transform::from_xyz(0.0, 90.0, 140.0)
+            .with_rotation(Quat::from_rotation_x(pitch.to_radians())),
+    ));
+}
+
+pub fn despawn_editor_camera(commands: &mut Commands, query: &Query<Entity, With<EditorCamera>>) {
+    for entity in query {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn fly_editor_camera(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_events: EventReader<MouseMotion>,
+    mut camera: Query<(&mut Transform, &mut EditorCamera)>,
+) {
+    let Ok((mut transform, mut camera)) = camera.single_mut() else {
+        return;
+    };
+
+    for event in mouse_events.read() {
+        if mouse.pressed(MouseButton::Right) {
+            camera.yaw -= event.delta.x * EDITOR_LOOK_SENSITIVITY;
+            camera.pitch =
+                (camera.pitch - event.delta.y * EDITOR_LOOK_SENSITIVITY).clamp(-89.9, 89.9);
+        }
+    }
+    transform.rotation = Quat::from_axis_angle(Vec3::Y, camera.yaw.to_radians())
+        * Quat::from_axis_angle(Vec3::X, camera.pitch.to_radians());
+
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let mut direction = Vec3::ZERO;
+    if keyboard.pressed(KeyCode::KeyW) {
+        direction += *transform.forward();
+    }
+    if keyboard.pressed(KeyCode::KeyS) {
+        direction -= *transform.forward();
+    }
+    if keyboard.pressed(KeyCode::KeyA) {
+        direction -= *transform.right();
+    }
+    if keyboard.pressed(KeyCode::KeyD) {
+        direction += *transform.right();
+    }
+    if keyboard.pressed(KeyCode::KeyE) {
+        direction += Vec3::Y;
+    }
+    if keyboard.pressed(KeyCode::KeyQ) {
+        direction -= Vec3::Y;
+    }
+
+    let mut speed = EDITOR_FLY_SPEED;
+    if keyboard.pressed(KeyCode::ShiftLeft) {
+        speed *= EDITOR_FAST_MULTIPLIER;
+    }
+    transform.translation += direction.normalize_or_zero() * speed * time.delta_secs();
+}
@@ -0,0 +1,117 @@
+use crate::{constants::editor::EDITOR_HISTORY_LIMIT, entities::map::map::Room};
+
+#[derive(Default)]
+pub struct EditHistory {
+    undo: Vec<Vec<Room>>,
+    redo: Vec<Vec<Room>>,
+}
+
+impl EditHistory {
+    pub fn record(&mut self, rooms: &[Room]) {
+        self.undo.push(rooms.to_vec());
+        if self.undo.len() > EDITOR_HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, rooms: &mut Vec<Room>) -> bool {
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(rooms, previous));
+        true
+    }
+
+    pub fn redo(&mut self, rooms: &mut Vec<Room>) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(rooms, next));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use bevy::prelude::*;
+
+    use super::*;
+
+    fn room(x: f32) -> Room {
+        Room {
+            name: "Room".to_string(),
+            wall_height: 10.0,
+            extension_x: 0.0,
+            extension_z: 0.0,
+            empty_side: HashSet::new(),
+            doors: Vec::new(),
+            offset: Vec3::new(x, 0.0, 0.0),
+            lighting: default(),
+            props: Vec::new(),
+            scatter: Vec::new(),
+            stairs: Vec::new(),
+            openings: Vec::new(),
+            theme: None,
+            wall_themes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut history = EditHistory::default();
+        let original = vec![room(0.0)];
+        let mut rooms = original.clone();
+
+        history.record(&rooms);
+        rooms[0].offset.x = 10.0;
+        history.record(&rooms);
+        rooms.push(room(50.0));
+        let edited = rooms.clone();
+
+        assert!(history.undo(&mut rooms));
+        assert!(history.undo(&mut rooms));
+        assert_eq!(rooms, original);
+        assert!(!history.undo(&mut rooms));
+
+        assert!(history.redo(&mut rooms));
+        assert!(history.redo(&mut rooms));
+        assert_eq!(rooms, edited);
+        assert!(!history.redo(&mut rooms));
+    }
+
+    #[test]
+    fn new_edit_discards_redo() {
+        let mut history = EditHistory::default();
+        let mut rooms = vec![room(0.0)];
+
+        history.record(&rooms);
+        rooms[0].offset.x = 10.0;
+        assert!(history.undo(&mut rooms));
+
+        history.record(&rooms);
+        rooms[0].offset.x = 20.0;
+        assert!(!history.redo(&mut rooms));
+        assert!(history.undo(&mut rooms));
+        assert_eq!(rooms, vec![room(0.0)]);
+    }
+
+    #[test]
+    fn oldest_edits_fall_off_the_history() {
+        let mut history = EditHistory::default();
+        let mut rooms = vec![room(0.0)];
+        for step in 1..=EDITOR_HISTORY_LIMIT + 5 {
+            history.record(&rooms);
+            rooms[0].offset.x = step as f32;
+        }
+
+        let mut undone = 0;
+        while history.undo(&mut rooms) {
+            undone += 1;
+        }
+        assert_eq!(undone, EDITOR_HISTORY_LIMIT);
+        assert_eq!(rooms, vec![room(5.0)]);
+    }
+}
@@ -0,0 +1,418 @@
+use std::{collections::HashSet, path::Path};
+
+use bevy::{color::palettes::css::YELLOW, prelude::*};
+
+use crate::{
+    constants::{
+        editor::{
+            EDITOR_AMBIENT_BRIGHTNESS, EDITOR_DOOR_RADIUS, EDITOR_FINE_STEP,
+            EDITOR_MIN_WALL_HEIGHT, EDITOR_MOVE_STEP,
+        },
+        map::{BASE_ROOM_SIZE, DEFAULT_LEVEL_PATH, ROOM_SIZE_STEP},
+    },
+    editor::{
+        camera::{EditorCamera, despawn_editor_camera, spawn_editor_camera},
+        history::EditHistory,
+    },
+    entities::map::{
+        builder::MapBuilder,
+        hazard::HazardDef,
+        level::{Level, LevelHandle, save_level},
+        map::{DoorOpening, MapEntity, Room},
+        mechanism::MechanismDef,
+        platform::PlatformDef,
+        room_tracking::RoomId,
+        streaming::{LevelStreaming, RoomMember},
+        theme::ThemeDef,
+        trigger::TriggerDef,
+        utility::WallType,
+        validation::validate_layout,
+        wall_mesh::DoorShape,
+    },
+    game_state::GameState,
+};
+
+#[derive(Resource)]
+pub struct EditorState {
+    rooms: Vec<Room>,
+    triggers: Vec<TriggerDef>,
+    hazards: Vec<HazardDef>,
+    mechanisms: Vec<MechanismDef>,
+    platforms: Vec<PlatformDef>,
+    themes: Vec<ThemeDef>,
+    selected: usize,
+    history: EditHistory,
+    dirty: HashSet<usize>,
+    unsaved: bool,
+    status: String,
+}
+
+#[derive(Component)]
+pub struct EditorHud;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SideKind {
+    Solid,
+    Empty,
+    Door,
+}
+
+fn side_kind(room: &Room, side: &WallType) -> SideKind {
+    if room.empty_side.contains(side) {
+        SideKind::Empty
+    } else if room.has_door(side) {
+        SideKind::Door
+    } else {
+        SideKind::Solid
+    }
+}
+
+fn cycle_side(room: &mut Room, side: WallType) {
+    match side_kind(room, &side) {
+        SideKind::Solid => {
+            room.empty_side.insert(side);
+        }
+        SideKind::Empty => {
+            room.empty_side.remove(&side);
+            room.doors.push(DoorOpening {
+                side,
+                position: 0.0,
+                width: EDITOR_DOOR_RADIUS * 2.0,
+                height: EDITOR_DOOR_RADIUS * 2.0,
+                locked: false,
+                secret: false,
+                shape: DoorShape::Arch,
+            });
+        }
+        SideKind::Door => room.doors.retain(|door| door.side != side),
+    }
+}
+
+impl EditorState {
+    fn new(level: &Level) -> Self {
+        Self {
+            rooms: level.rooms.clone(),
+            triggers: level.triggers.clone(),
+            hazards: level.hazards.clone(),
+            mechanisms: level.mechanisms.clone(),
+            platforms: level.platforms.clone(),
+            themes: level.themes.clone(),
+            selected: 0,
+            history: EditHistory::default(),
+            dirty: (0..level.rooms.len()).collect(),
+            unsaved: false,
+            status: String::new(),
+        }
+    }
+
+    fn edit(&mut self, change: impl FnOnce(&mut Room)) {
+        let Some(room) = self.rooms.get(self.selected) else {
+            return;
+        };
+
+        let mut edited = room.clone();
+        change(&mut edited);
+        if edited == *room {
+            return;
+        }
+
+        self.history.record(&self.rooms);
+        self.rooms[self.selected] = edited;
+        self.dirty.insert(self.selected);
+        self.unsaved = true;
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.dirty.extend(0..self.rooms.len());
+        self.unsaved = true;
+    }
+
+    fn save(&mut self) {
+        let path = Path::new("assets").join(DEFAULT_LEVEL_PATH);
+        let level = Level {
+            rooms: self.rooms.clone(),
+            triggers: self.triggers.clone(),
+            hazards: self.hazards.clone(),
+            mechanisms: self.mechanisms.clone(),
+            platforms: self.platforms.clone(),
+            themes: self.themes.clone(),
+        };
+
+        match save_level(&path, &level) {
+            Ok(()) => {
+                info!("Saved level to {}", path.display());
+                self.unsaved = false;
+                self.status = format!("Saved {}", path.display());
+            }
+            Err(error) => {
+                error!("{error}");
+                self.status = error.to_string();
+            }
+        }
+    }
+}
+
+pub fn editor_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ambient: ResMut<AmbientLight>,
+) {
+    commands.insert_resource(LevelHandle(asset_server.load(DEFAULT_LEVEL_PATH)));
+    ambient.brightness = EDITOR_AMBIENT_BRIGHTNESS;
+    spawn_editor_camera(&mut commands);
+
+    commands.spawn((
+        Text::new("Loading level..."),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgba(0.9, 0.9, 0.9, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        EditorHud,
+    ));
+}
+
+pub fn editor_load(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    level_handle: Res<LevelHandle>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.get(&level_handle.0) else {
+        return;
+    };
+
+    commands.insert_resource(MapBuilder::new(
+        &mut materials,
+        &asset_server,
+        &level.themes,
+    ));
+    commands.insert_resource(LevelStreaming::new(level_handle.0.clone(), &level.rooms));
+    commands.insert_resource(EditorState::new(level));
+}
+
+pub fn editor_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<EditorState>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        game_state.set(GameState::Menu);
+        return;
+    }
+
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let step = if keyboard.pressed(KeyCode::AltLeft) {
+        EDITOR_FINE_STEP
+    } else {
+        EDITOR_MOVE_STEP
+    };
+
+    if ctrl {
+        if keyboard.just_pressed(KeyCode::KeyS) {
+            state.save();
+        }
+        let undo = keyboard.just_pressed(KeyCode::KeyZ) && !shift;
+        let redo =
+            keyboard.just_pressed(KeyCode::KeyY) || (keyboard.just_pressed(KeyCode::KeyZ) && shift);
+        let state = &mut *state;
+        if (undo && state.history.undo(&mut state.rooms))
+            || (redo && state.history.redo(&mut state.rooms))
+        {
+            state.mark_all_dirty();
+        }
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::Tab) && !state.rooms.is_empty() {
+        let count = state.rooms.len();
+        state.selected = if shift {
+            (state.selected + count - 1) % count
+        } else {
+            (state.selected + 1) % count
+        };
+    }
+
+    let mut movement = Vec3::ZERO;
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        movement.z -= step;
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        movement.z += step;
+    }
+    if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        movement.x -= step;
+    }
+    if keyboard.just_pressed(KeyCode::ArrowRight) {
+        movement.x += step;
+    }
+    if keyboard.just_pressed(KeyCode::PageUp) {
+        movement.y += step;
+    }
+    if keyboard.just_pressed(KeyCode::PageDown) {
+        movement.y -= step;
+    }
+    if movement != Vec3::ZERO {
+        state.edit(|room| room.offset += movement);
+    }
+
+    let min_extension = ROOM_SIZE_STEP - BASE_ROOM_SIZE;
+    let mut extension = Vec2::ZERO;
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        extension.x += step;
+    }
+    if keyboard.just_pressed(KeyCode::KeyG) {
+        extension.x -= step;
+    }
+    if keyboard.just_pressed(KeyCode::KeyY) {
+        extension.y += step;
+    }
+    if keyboard.just_pressed(KeyCode::KeyH) {
+        extension.y -= step;
+    }
+    if extension != Vec2::ZERO {
+        state.edit(|room| {
+            room.extension_x = (room.extension_x + extension.x).max(min_extension);
+            room.extension_z = (room.extension_z + extension.y).max(min_extension);
+        });
+    }
+
+    let mut height = 0.0;
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        height += EDITOR_FINE_STEP;
+    }
+    if keyboard.just_pressed(KeyCode::KeyF) {
+        height -= EDITOR_FINE_STEP;
+    }
+    if height != 0.0 {
+        state.edit(|room| {
+            let lowest = room
+                .doors
+                .iter()
+                .map(|door| door.height)
+                .fold(EDITOR_MIN_WALL_HEIGHT, f32::max);
+            room.wall_height = (room.wall_height + height).max(lowest);
+        });
+    }
+
+    let side_keys = [
+        (KeyCode::Digit1, WallType::NORTH),
+        (KeyCode::Digit2, WallType::SOUTH),
+        (KeyCode::Digit3, WallType::WEST),
+        (KeyCode::Digit4, WallType::EAST),
+    ];
+    for (key, side) in side_keys {
+        if keyboard.just_pressed(key) {
+            state.edit(|room| cycle_side(room, side));
+        }
+    }
+}
+
+pub fn rebuild_dirty_rooms(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut builder: ResMut<MapBuilder>,
+    mut streaming: ResMut<LevelStreaming>,
+    mut state: ResMut<EditorState>,
+    members: Query<(Entity, &RoomMember)>,
+) {
+    if state.dirty.is_empty() {
+        return;
+    }
+
+    let dirty = std::mem::take(&mut state.dirty);
+    for (entity, RoomMember(RoomId(index))) in members.iter() {
+        if dirty.contains(index) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for index in dirty {
+        streaming.reload_room(
+            &mut commands,
+            &mut meshes,
+            &mut builder,
+            &state.rooms[index],
+            RoomId(index),
+        );
+    }
+}
+
+pub fn draw_selected_room(state: Res<EditorState>, mut gizmos: Gizmos) {
+    let Some(room) = state.rooms.get(state.selected) else {
+        return;
+    };
+
+    let (min, max) = room.get_bounds();
+    gizmos.cuboid(
+        Transform::from_translation((min + max) / 2.0).with_scale(max - min),
+        YELLOW,
+    );
+}
+
+pub fn update_editor_hud(state: Res<EditorState>, mut hud: Query<&mut Text, With<EditorHud>>) {
+    if !state.is_changed() {
+        return;
+    }
+    let Ok(mut text) = hud.single_mut() else {
+        return;
+    };
+    let Some(room) = state.rooms.get(state.selected) else {
+        return;
+    };
+
+    let sides: Vec<String> = WallType::all()
+        .iter()
+        .map(|side| format!("{side:?}: {:?}", side_kind(room, side)))
+        .collect();
+    let issues = validate_layout(&state.rooms, &state.mechanisms).len();
+
+    text.0 = format!(
+        "{}{} [{}/{}]\n\
+         offset ({}, {}, {})  extension ({}, {})  wall height {}\n\
+         {}\n\
+         {} layout issues  {}\n\
+         Tab/Shift+Tab select | Arrows, PgUp/PgDn move | T/G, Y/H extend | R/F height\n\
+         1-4 cycle N/S/W/E side | Alt fine step | Ctrl+Z/Ctrl+Y undo/redo | Ctrl+S save | Esc exit",
+        room.name,
+        if state.unsaved { " *" } else { "" },
+        state.selected + 1,
+        state.rooms.len(),
+        room.offset.x,
+        room.offset.y,
+        room.offset.z,
+        room.extension_x,
+        room.extension_z,
+        room.wall_height,
+        sides.join("  "),
+        issues,
+        state.status,
+    );
+}
+
+pub fn editor_cleanup(
+    mut commands: Commands,
+    map_query: Query<Entity, With<MapEntity>>,
+    hud_query: Query<Entity, With<EditorHud>>,
+    camera_query: Query<Entity, With<EditorCamera>>,
+    mut ambient: ResMut<AmbientLight>,
+) {
+    for entity in map_query.iter().chain(hud_query.iter()) {
+        commands.entity(entity).despawn();
+    }
+    despawn_editor_camera(&mut commands, &camera_query);
+
+    *ambient = AmbientLight::default();
+    commands.remove_resource::<EditorState>();
+    commands.remove_resource::<MapBuilder>();
+    commands.remove_resource::<LevelStreaming>();
+    commands.remove_resource::<LevelHandle>();
+}
@@ -0,0 +1,34 @@
+pub mod camera;
+mod history;
+pub mod level_editor;
+
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use camera::fly_editor_camera;
+use level_editor::{
+    EditorState, draw_selected_room, editor_cleanup, editor_input, editor_load, editor_setup,
+    rebuild_dirty_rooms, update_editor_hud,
+};
+
+pub fn editor_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Editor), editor_setup)
+        .add_systems(
+            Update,
+            editor_load
+                .run_if(in_state(GameState::Editor).and(not(resource_exists::<EditorState>))),
+        )
+        .add_systems(
+            Update,
+            (
+                fly_editor_camera,
+                editor_input,
+                rebuild_dirty_rooms,
+                draw_selected_room,
+                update_editor_hud,
+            )
+                .chain()
+                .run_if(in_state(GameState::Editor).and(resource_exists::<EditorState>)),
+        )
+        .add_systems(OnExit(GameState::Editor), editor_cleanup);
+}
@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use crate::{
+    constants::map::DEFAULT_THEME,
+    entities::map::{
+        map::MapEntity,
+        room_tracking::RoomId,
+        streaming::RoomMember,
+        theme::{ThemeDef, ThemeMaterials, builtin_themes, load_repeating_texture},
+    },
+};
+
+pub struct MapMaterials {
+    pub wall: Handle<StandardMaterial>,
+    pub door: Handle<StandardMaterial>,
+    pub hazard: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct MapBuildStats {
+    pub entities: usize,
+    pub meshes: usize,
+    pub materials: usize,
+    pub lights: usize,
+}
+
+#[derive(Resource)]
+pub struct MapBuilder {
+    pub materials: MapMaterials,
+    pub stats: MapBuildStats,
+    themes: HashMap<String, ThemeMaterials>,
+    missing_themes: HashSet<String>,
+    mesh_cache: HashMap<(&'static str, [u32; 3]), Handle<Mesh>>,
+    scene_cache: HashMap<String, Handle<Scene>>,
+    asset_server: AssetServer,
+}
+
+impl MapBuilder {
+    pub fn new(
+        materials: &mut Assets<StandardMaterial>,
+        asset_server: &AssetServer,
+        level_themes: &[ThemeDef],
+    ) -> Self {
+        let bricks_texture = load_repeating_texture(asset_server, "map/bricks.png", true);
+        let mut stats = MapBuildStats::default();
+        let mut add = |material: StandardMaterial| {
+            stats.materials += 1;
+            materials.add(material)
+        };
+
+        let map_materials = MapMaterials {
+            wall: add(StandardMaterial {
+                base_color_texture: Some(bricks_texture),
+                ..default()
+            }),
+            door: add(StandardMaterial {
+                base_color: Color::srgb(0.18, 0.16, 0.15),
+                perceptual_roughness: 0.6,
+                metallic: 0.7,
+                ..default()
+            }),
+            hazard: add(StandardMaterial {
+                base_color: Color::srgba(0.3, 0.8, 0.2, 0.7),
+                emissive: LinearRgba::rgb(0.2, 1.0, 0.1),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+        };
+
+        let themes: HashMap<String, ThemeMaterials> = builtin_themes()
+            .into_iter()
+            .chain(level_themes.iter().cloned())
+            .map(|theme| {
+                let materials = ThemeMaterials {
+                    wall: add(theme.wall.material(asset_server)),
+                    floor: add(theme.floor.material(asset_server)),
+                    roof: add(theme.roof.material(asset_server)),
+                };
+                (theme.name, materials)
+            })
+            .collect();
+
+        Self {
+            materials: map_materials,
+            stats,
+            themes,
+            missing_themes: HashSet::new(),
+            mesh_cache: HashMap::new(),
+            scene_cache: HashMap::new(),
+            asset_server: asset_server.clone(),
+        }
+    }
+
+    // Falls back to the default theme, warning once for each unknown name.
+    pub fn theme(&mut self, name: Option<&str>) -> ThemeMaterials {
+        let name = name.unwrap_or(DEFAULT_THEME);
+        if let Some(theme) = self.themes.get(name) {
+            return theme.clone();
+        }
+
+        if self.missing_themes.insert(name.to_string()) {
+            warn!("Unknown theme `{name}`, using `{DEFAULT_THEME}`");
+        }
+        self.themes[DEFAULT_THEME].clone()
+    }
+
+    pub fn add_mesh(&mut self, meshes: &mut Assets<Mesh>, mesh: Mesh) -> Handle<Mesh> {
+        self.stats.meshes += 1;
+        meshes.add(mesh)
+    }
+
+    pub fn cached_mesh(
+        &mut self,
+        meshes: &mut Assets<Mesh>,
+        kind: &'static str,
+        size: Vec3,
+        build: impl FnOnce() -> Mesh,
+    ) -> Handle<Mesh> {
+        let key = (kind, size.to_array().map(f32::to_bits));
+        if let Some(handle) = self.mesh_cache.get(&key) {
+            return handle.clone();
+        }
+
+        let handle = self.add_mesh(meshes, build());
+        self.mesh_cache.insert(key, handle.clone());
+        handle
+    }
+
+    pub fn load_scene(&mut self, path: &str) -> Handle<Scene> {
+        self.scene_cache
+            .entry(path.to_string())
+            .or_insert_with(|| self.asset_server.load(path.to_string()))
+            .clone()
+    }
+
+    pub fn spawn<'a>(
+        &mut self,
+        commands: &'a mut Commands,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'a> {
+        self.stats.entities += 1;
+        commands.spawn((bundle, MapEntity))
+    }
+
+    pub fn spawn_in_room<'a>(
+        &mut self,
+        commands: &'a mut Commands,
+        room: RoomId,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'a> {
+        self.spawn(commands, (bundle, RoomMember(room)))
+    }
+}
+
+pub fn tiled_box(center: Vec3, size: Vec3) -> Mesh {
+    let mut mesh = Mesh::from(Cuboid::from_size(size)).translated_by(center);
+
+    let positions = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|values| values.as_float3())
+        .unwrap_or_default();
+    let normals = mesh
+        .attribute(Mesh::ATTRIBUTE_NORMAL)
+        .and_then(|values| values.as_float3())
+        .unwrap_or_default();
+
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .zip(normals)
+        .map(|(position, normal)| tiled_uv(Vec3::from(*position), Vec3::from(*normal)))
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.with_generated_tangents()
+        .expect("boxes have positions, normals and uvs")
+}
+
+// Projects a point onto the plane its face mostly points along, so textures
+// line up across neighbouring pieces. The uvs are in world units and each
+// material scales them to its own tiling.
+pub fn tiled_uv(position: Vec3, normal: Vec3) -> [f32; 2] {
+    let uv = if normal.x.abs() > 0.5 {
+        Vec2::new(position.z, -position.y)
+    } else if normal.z.abs() > 0.5 {
+        Vec2::new(position.x, -position.y)
+    } else {
+        Vec2::new(position.x, position.z)
+    };
+    uv.to_array()
+}
+
+pub fn merge_meshes(parts: impl IntoIterator<Item = Mesh>) -> Option<Mesh> {
+    let mut parts = parts.into_iter();
+    let mut merged = parts.next()?;
+    for part in parts {
+        merged
+            .merge(&part)
+            .expect("map pieces share the same vertex layout");
+    }
+    Some(merged)
+}
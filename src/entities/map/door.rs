@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    constants::map::{DOOR_AUTO_OPEN_DISTANCE, DOOR_SLIDE_SPEED, DOOR_THICKNESS},
+    entities::map::{
+        builder::MapBuilder,
+        map::{DoorOpening, Room},
+        room_tracking::RoomId,
+        streaming::LevelStreaming,
+        utility::WallType,
+        wall_mesh::{DoorShape, WallMeshBuilder},
+    },
+    player::player::Player,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoorState {
+    Open,
+    Closed,
+    Locked,
+}
+
+#[derive(Component, Debug)]
+pub struct Door {
+    pub room: String,
+    pub index: usize,
+    pub side: WallType,
+    pub state: DoorState,
+    closed_translation: Vec3,
+    slide: Vec3,
+    open_amount: f32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoorAction {
+    Open,
+    Close,
+    Lock,
+    Unlock,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DoorCommand {
+    pub room: String,
+    pub side: WallType,
+    // Index into the room's doors. `None` addresses every door on the side.
+    pub door: Option<usize>,
+    pub action: DoorAction,
+}
+
+impl DoorCommand {
+    pub fn targets(&self, side: WallType, index: usize) -> bool {
+        self.side == side && self.door.is_none_or(|door| door == index)
+    }
+}
+
+impl DoorState {
+    pub fn after(self, action: DoorAction) -> Option<DoorState> {
+        match (self, action) {
+            (DoorState::Locked, DoorAction::Open) => None,
+            (DoorState::Locked, DoorAction::Unlock) => Some(DoorState::Closed),
+            (_, DoorAction::Open) => Some(DoorState::Open),
+            (_, DoorAction::Lock) => Some(DoorState::Locked),
+            (DoorState::Locked, DoorAction::Close) => Some(DoorState::Locked),
+            (_, DoorAction::Close) => Some(DoorState::Closed),
+            (state, DoorAction::Unlock) => Some(state),
+        }
+    }
+
+    pub fn initial(opening: &DoorOpening) -> DoorState {
+        if opening.locked {
+            DoorState::Locked
+        } else {
+            DoorState::Closed
+        }
+    }
+}
+
+impl Door {
+    fn apply(&mut self, action: DoorAction) -> bool {
+        let Some(next) = self.state.after(action) else {
+            warn!("{:?} door of `{}` is locked", self.side, self.room);
+            return false;
+        };
+
+        let changed = next != self.state;
+        self.state = next;
+        changed
+    }
+}
+
+fn set_collider(commands: &mut Commands, entity: Entity, state: DoorState) {
+    if state == DoorState::Open {
+        commands.entity(entity).insert(ColliderDisabled);
+    } else {
+        commands.entity(entity).remove::<ColliderDisabled>();
+    }
+}
+
+pub fn spawn_room_doors(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+    saved: &HashMap<usize, DoorState>,
+) {
+    for (index, opening) in room.doors.iter().enumerate() {
+        if opening.secret || room.empty_side.contains(&opening.side) {
+            continue;
+        }
+
+        let state = saved
+            .get(&index)
+            .copied()
+            .unwrap_or(DoorState::initial(opening));
+        let closed_translation = room.door_center(opening) + Vec3::Y * opening.height / 2.0;
+        let slide = Vec3::NEG_Y * opening.height;
+        let open_amount = if state == DoorState::Open { 1.0 } else { 0.0 };
+        let rotation = Quat::from_rotation_arc(Vec3::Z, opening.side.normal());
+
+        let door_size = Vec3::new(opening.width, opening.height, DOOR_THICKNESS);
+        let mut panel = WallMeshBuilder::default();
+        panel.add_panel(&opening.shape, door_size);
+        let Some((mesh, collider)) = panel.build() else {
+            continue;
+        };
+        let door_mesh = match opening.shape {
+            DoorShape::Rectangle => builder.cached_mesh(meshes, "door", door_size, || mesh),
+            DoorShape::Arch => builder.cached_mesh(meshes, "arched door", door_size, || mesh),
+            DoorShape::Outline(_) => builder.add_mesh(meshes, mesh),
+        };
+        let door_material = builder.materials.door.clone();
+
+        let entity = builder
+            .spawn_in_room(
+                commands,
+                room_id,
+                (
+                    Mesh3d(door_mesh),
+                    MeshMaterial3d(door_material),
+                    Transform::from_translation(closed_translation + slide * open_amount)
+                        .with_rotation(rotation),
+                    GlobalTransform::default(),
+                    collider,
+                    Door {
+                        room: room.name.clone(),
+                        index,
+                        side: opening.side,
+                        state,
+                        closed_translation,
+                        slide,
+                        open_amount,
+                    },
+                ),
+            )
+            .id();
+        set_collider(commands, entity, state);
+    }
+}
+
+pub fn apply_door_commands(
+    mut commands: Commands,
+    mut door_commands: EventReader<DoorCommand>,
+    mut doors: Query<(Entity, &mut Door)>,
+    mut streaming: Option<ResMut<LevelStreaming>>,
+) {
+    for command in door_commands.read() {
+        let mut found = false;
+
+        for (entity, mut door) in doors.iter_mut() {
+            if door.room != command.room || !command.targets(door.side, door.index) {
+                continue;
+            }
+
+            found = true;
+            if door.apply(command.action) {
+                set_collider(&mut commands, entity, door.state);
+            }
+        }
+
+        let deferred = !found
+            && streaming
+                .as_mut()
+                .is_some_and(|streaming| streaming.defer_door_command(command));
+        if deferred {
+            debug!(
+                "Deferred {:?} of {:?} door in unloaded room `{}`",
+                command.action, command.side, command.room
+            );
+        } else if !found {
+            warn!(
+                "No {:?} door in `{}` for {:?}",
+                command.side, command.room, command.action
+            );
+        }
+    }
+}
+
+pub fn open_doors_near_player(
+    mut commands: Commands,
+    player: Query<&Transform, (With<Player>, With<KinematicCharacterController>)>,
+    mut doors: Query<(Entity, &mut Door)>,
+) {
+    let Ok(player_transform) = player.single() else {
+        return;
+    };
+
+    for (entity, mut door) in doors.iter_mut() {
+        if door.state != DoorState::Closed {
+            continue;
+        }
+
+        let distance = door
+            .closed_translation
+            .distance(player_transform.translation);
+        if distance <= DOOR_AUTO_OPEN_DISTANCE && door.apply(DoorAction::Open) {
+            set_collider(&mut commands, entity, door.state);
+        }
+    }
+}
+
+pub fn animate_doors(time: Res<Time>, mut doors: Query<(&mut Door, &mut Transform)>) {
+    for (mut door, mut transform) in doors.iter_mut() {
+        let target = if door.state == DoorState::Open {
+            1.0
+        } else {
+            0.0
+        };
+        if door.open_amount == target {
+            continue;
+        }
+
+        let step = DOOR_SLIDE_SPEED * time.delta_secs();
+        door.open_amount = if door.open_amount < target {
+            (door.open_amount + step).min(target)
+        } else {
+            (door.open_amount - step).max(target)
+        };
+        transform.translation = door.closed_translation + door.slide * door.open_amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(door: Option<usize>) -> DoorCommand {
+        DoorCommand {
+            room: "Hall".to_string(),
+            side: WallType::WEST,
+            door,
+            action: DoorAction::Open,
+        }
+    }
+
+    #[test]
+    fn command_addresses_a_side_or_one_door() {
+        assert!(command(None).targets(WallType::WEST, 0));
+        assert!(command(None).targets(WallType::WEST, 3));
+        assert!(!command(None).targets(WallType::EAST, 0));
+
+        assert!(command(Some(3)).targets(WallType::WEST, 3));
+        assert!(!command(Some(3)).targets(WallType::WEST, 0));
+        assert!(!command(Some(3)).targets(WallType::EAST, 3));
+    }
+}
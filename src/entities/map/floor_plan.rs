@@ -0,0 +1,230 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::map::{FLOOR_PLAN_ASCII_CELL, FLOOR_PLAN_SVG_MARGIN, FLOOR_PLAN_SVG_SCALE},
+    entities::map::{map::Room, utility::WallType},
+};
+
+struct Plan {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Plan {
+    fn new(rooms: &[Room]) -> Self {
+        let mut min = Vec2::splat(f32::MAX);
+        let mut max = Vec2::splat(f32::MIN);
+        for room in rooms {
+            let (room_min, room_max) = room.get_bounds();
+            min = min.min(room_min.xz());
+            max = max.max(room_max.xz());
+        }
+        if rooms.is_empty() {
+            min = Vec2::ZERO;
+            max = Vec2::ZERO;
+        }
+        Self { min, max }
+    }
+}
+
+// Both end points of a wall segment, `from`..`to` along the wall.
+fn wall_segment(room: &Room, side: &WallType, from: f32, to: f32) -> (Vec2, Vec2) {
+    let center = room.wall_center(side);
+    let along = side.along();
+    ((center + along * from).xz(), (center + along * to).xz())
+}
+
+fn openings(room: &Room) -> Vec<(Vec2, Vec2, char)> {
+    let mut result = Vec::new();
+    for side in WallType::all() {
+        let half_length = room.wall_half_length(side);
+        if room.empty_side.contains(side) {
+            let (start, end) = wall_segment(room, side, -half_length, half_length);
+            result.push((start, end, '.'));
+            continue;
+        }
+        for door in room.doors_on(side) {
+            let (start, end) = wall_segment(
+                room,
+                side,
+                door.position - door.width / 2.0,
+                door.position + door.width / 2.0,
+            );
+            let symbol = if door.secret {
+                'S'
+            } else if door.locked {
+                'L'
+            } else {
+                'D'
+            };
+            result.push((start, end, symbol));
+        }
+    }
+    result
+}
+
+// Open sides are drawn first so the doors leading into them stay visible.
+fn all_openings(rooms: &[Room]) -> Vec<(Vec2, Vec2, char)> {
+    let mut result: Vec<_> = rooms.iter().flat_map(openings).collect();
+    result.sort_by_key(|(_, _, glyph)| *glyph != '.');
+    result
+}
+
+struct Canvas {
+    cells: Vec<Vec<char>>,
+    origin: Vec2,
+    top: f32,
+}
+
+impl Canvas {
+    fn cell(&self, point: Vec2) -> (usize, usize) {
+        let column = ((point.x - self.origin.x) / FLOOR_PLAN_ASCII_CELL).round() as usize;
+        let row = ((self.top - point.y) / FLOOR_PLAN_ASCII_CELL).round() as usize;
+        (column, row)
+    }
+
+    fn put(&mut self, column: usize, row: usize, glyph: char) {
+        if let Some(cell) = self.cells.get_mut(row).and_then(|r| r.get_mut(column)) {
+            *cell = glyph;
+        }
+    }
+
+    fn line(&mut self, start: Vec2, end: Vec2, glyph: char) {
+        let (c0, r0) = self.cell(start);
+        let (c1, r1) = self.cell(end);
+        for row in r0.min(r1)..=r0.max(r1) {
+            for column in c0.min(c1)..=c0.max(c1) {
+                self.put(column, row, glyph);
+            }
+        }
+    }
+
+    fn text(&mut self, column: usize, row: usize, text: &str, max_len: usize) {
+        for (i, glyph) in text.chars().take(max_len).enumerate() {
+            self.put(column + i, row, glyph);
+        }
+    }
+}
+
+pub fn render_ascii(rooms: &[Room]) -> String {
+    let plan = Plan::new(rooms);
+    let size = ((plan.max - plan.min) / FLOOR_PLAN_ASCII_CELL).round();
+    let mut canvas = Canvas {
+        cells: vec![vec![' '; size.x as usize + 1]; size.y as usize + 1],
+        origin: plan.min,
+        top: plan.max.y,
+    };
+
+    for room in rooms {
+        let (min, max) = room.get_bounds();
+        let (min, max) = (min.xz(), max.xz());
+        canvas.line(min, Vec2::new(max.x, min.y), '-');
+        canvas.line(Vec2::new(min.x, max.y), max, '-');
+        canvas.line(min, Vec2::new(min.x, max.y), '|');
+        canvas.line(Vec2::new(max.x, min.y), max, '|');
+        for corner in [min, max, Vec2::new(min.x, max.y), Vec2::new(max.x, min.y)] {
+            let (column, row) = canvas.cell(corner);
+            canvas.put(column, row, '+');
+        }
+    }
+
+    for (start, end, glyph) in all_openings(rooms) {
+        canvas.line(start, end, glyph);
+    }
+
+    for (index, room) in rooms.iter().enumerate() {
+        let (min, max) = room.get_bounds();
+        let (left, top) = canvas.cell(Vec2::new(min.x, max.z));
+        let (right, _) = canvas.cell(Vec2::new(max.x, max.z));
+        let width = right.saturating_sub(left + 1);
+        canvas.text(left + 1, top + 1, &index.to_string(), width);
+    }
+
+    let mut output = String::new();
+    for row in &canvas.cells {
+        output.push_str(row.iter().collect::<String>().trim_end());
+        output.push('\n');
+    }
+
+    output.push('\n');
+    for (index, room) in rooms.iter().enumerate() {
+        let size = room.half_extents() * 2.0;
+        let _ = writeln!(
+            output,
+            "{index:>3}  {}  at ({}, {}, {})  size {}x{}",
+            room.name, room.offset.x, room.offset.y, room.offset.z, size.x, size.y
+        );
+    }
+    output.push_str("\nlegend: D door, L locked door, S secret wall, . open side\n");
+    output
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn render_svg(rooms: &[Room]) -> String {
+    let plan = Plan::new(rooms);
+    let size = (plan.max - plan.min) * FLOOR_PLAN_SVG_SCALE + FLOOR_PLAN_SVG_MARGIN * 2.0;
+    let project = |point: Vec2| {
+        Vec2::new(
+            (point.x - plan.min.x) * FLOOR_PLAN_SVG_SCALE + FLOOR_PLAN_SVG_MARGIN,
+            (plan.max.y - point.y) * FLOOR_PLAN_SVG_SCALE + FLOOR_PLAN_SVG_MARGIN,
+        )
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"##,
+        size.x, size.y, size.x, size.y
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="100%" height="100%" fill="#1b1b1f"/>"##
+    );
+
+    for room in rooms {
+        let (min, max) = room.get_bounds();
+        let top_left = project(Vec2::new(min.x, max.z));
+        let bottom_right = project(Vec2::new(max.x, min.z));
+        let extent = bottom_right - top_left;
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#2c2c34" stroke="#d0d0d0" stroke-width="2"/>"##,
+            top_left.x, top_left.y, extent.x, extent.y
+        );
+    }
+
+    for (start, end, glyph) in all_openings(rooms) {
+        let (start, end) = (project(start), project(end));
+        let style = match glyph {
+            '.' => r##"stroke="#2c2c34" stroke-width="3" stroke-dasharray="6 4""##,
+            'L' => r##"stroke="#e05050" stroke-width="5""##,
+            _ => r##"stroke="#60c060" stroke-width="5""##,
+        };
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{}" y1="{}" x2="{}" y2="{}" {style}/>"##,
+            start.x, start.y, end.x, end.y
+        );
+    }
+
+    for room in rooms {
+        let center = project(room.offset.xz());
+        let _ = writeln!(
+            svg,
+            r##"<text x="{}" y="{}" fill="#f0f0f0" font-family="monospace" font-size="12" text-anchor="middle">{}</text>"##,
+            center.x,
+            center.y,
+            escape(&room.name)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+use thiserror::Error;
+
+use crate::{
+    constants::map::{
+        BASE_ROOM_SIZE, ENTRY_ROOM_NAME, GENERATED_DOOR_RADIUS, GENERATED_WALL_HEIGHT,
+        MAX_PLACEMENT_ATTEMPTS, ROOM_SIZE_STEP, STAIR_WALL_MARGIN, STAIR_WIDTH, WALL_THICKNESS,
+    },
+    entities::map::{
+        lighting::{Flicker, FogProfile, LightingProfile},
+        map::{DoorOpening, Room},
+        props::{PropBody, PropDef, PropShape, ScatterPlacement, ScatterRule},
+        stairs::{StairDef, StairKind, Surface, VerticalOpening, storey_height},
+        utility::WallType,
+        wall_mesh::DoorShape,
+    },
+};
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct DungeonSeed(pub u64);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RoomRole {
+    Entry,
+    Enemy,
+    Puzzle,
+    Reward,
+    Boss,
+}
+
+impl RoomRole {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RoomRole::Entry => ENTRY_ROOM_NAME,
+            RoomRole::Enemy => "Enemy Room",
+            RoomRole::Puzzle => "Puzzle Room",
+            RoomRole::Reward => "Reward Room",
+            RoomRole::Boss => "Boss Room",
+        }
+    }
+
+    pub fn lighting(&self) -> LightingProfile {
+        match self {
+            RoomRole::Entry => LightingProfile {
+                ambient: 80.0,
+                flicker: Flicker::Steady,
+                ..default()
+            },
+            RoomRole::Enemy => LightingProfile {
+                ambient: 20.0,
+                torches_per_wall: 1,
+                ..default()
+            },
+            RoomRole::Puzzle => LightingProfile {
+                torch_color: (0.45, 0.6, 1.0),
+                flicker: Flicker::Pulse,
+                ..default()
+            },
+            RoomRole::Reward => LightingProfile {
+                ambient: 60.0,
+                torch_color: (1.0, 0.85, 0.45),
+                ..default()
+            },
+            RoomRole::Boss => LightingProfile {
+                ambient: 10.0,
+                torches_per_wall: 3,
+                torch_color: (1.0, 0.3, 0.2),
+                fog: Some(FogProfile {
+                    color: (0.08, 0.02, 0.02),
+                    start: 10.0,
+                    end: 80.0,
+                }),
+                flicker: Flicker::Pulse,
+                ..default()
+            },
+        }
+    }
+
+    pub fn scatter(&self) -> Vec<ScatterRule> {
+        match self {
+            RoomRole::Entry => Vec::new(),
+            RoomRole::Enemy => vec![barrels(4), crates(2, ScatterPlacement::Anywhere)],
+            RoomRole::Puzzle => vec![crates(3, ScatterPlacement::AlongWalls)],
+            RoomRole::Reward => vec![crates(4, ScatterPlacement::AlongWalls)],
+            RoomRole::Boss => vec![barrels(8)],
+        }
+    }
+}
+
+fn barrels(count: u32) -> ScatterRule {
+    ScatterRule {
+        prop: PropDef {
+            scene: "props/barrel.glb#Scene0".to_string(),
+            position: (0.0, 0.0, 0.0),
+            rotation: (0.0, 0.0, 0.0),
+            scale: 1.0,
+            collider: Some(PropShape::Cylinder {
+                half_height: 0.6,
+                radius: 0.45,
+            }),
+            body: PropBody::Dynamic,
+        },
+        count,
+        placement: ScatterPlacement::AlongWalls,
+        seed: 0,
+    }
+}
+
+fn crates(count: u32, placement: ScatterPlacement) -> ScatterRule {
+    ScatterRule {
+        prop: PropDef {
+            scene: "props/crate.glb#Scene0".to_string(),
+            position: (0.0, 0.0, 0.0),
+            rotation: (0.0, 0.0, 0.0),
+            scale: 1.0,
+            collider: Some(PropShape::Cuboid(0.6, 0.6, 0.6)),
+            body: PropBody::Static,
+        },
+        count,
+        placement,
+        seed: 0,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DungeonConstraints {
+    pub room_count: usize,
+    pub role_weights: Vec<(RoomRole, u32)>,
+    pub boss_room: bool,
+    pub min_extension: f32,
+    pub max_extension: f32,
+    pub floors: usize,
+    pub stair_chance: f64,
+}
+
+impl Default for DungeonConstraints {
+    fn default() -> Self {
+        Self {
+            room_count: 13,
+            role_weights: vec![
+                (RoomRole::Enemy, 5),
+                (RoomRole::Puzzle, 2),
+                (RoomRole::Reward, 2),
+            ],
+            boss_room: true,
+            min_extension: -20.0,
+            max_extension: 20.0,
+            floors: 2,
+            stair_chance: 0.15,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GeneratorError {
+    #[error("room count must be at least 1")]
+    NoRooms,
+    #[error("floor count must be at least 1")]
+    NoFloors,
+    #[error("role weights must contain at least one non-boss role with a positive weight")]
+    NoRoles,
+    #[error("extension range {min}..={max} is empty or leaves rooms with no floor")]
+    InvalidSizeRange { min: f32, max: f32 },
+    #[error("could only place {placed} of {requested} rooms without overlaps")]
+    NoSpace { placed: usize, requested: usize },
+}
+
+struct PlacedRoom {
+    extension: Vec2,
+    offset: Vec3,
+    depth: usize,
+    floor: usize,
+    empty_side: HashSet<WallType>,
+    doors: Vec<DoorOpening>,
+    stairs: Vec<StairDef>,
+    openings: Vec<VerticalOpening>,
+}
+
+fn half_extents(extension: Vec2) -> Vec2 {
+    Vec2::splat(BASE_ROOM_SIZE) + extension
+}
+
+fn depth_towards(extension: Vec2, side: &WallType) -> f32 {
+    let normal = side.normal();
+    half_extents(extension).dot(Vec2::new(normal.x, normal.z).abs())
+}
+
+fn wall_length(extension: Vec2, side: &WallType) -> f32 {
+    let normal = side.normal();
+    half_extents(extension).dot(Vec2::new(normal.z, normal.x).abs())
+}
+
+fn generated_door(side: WallType) -> DoorOpening {
+    DoorOpening {
+        side,
+        position: 0.0,
+        width: GENERATED_DOOR_RADIUS * 2.0,
+        height: GENERATED_DOOR_RADIUS * 2.0,
+        locked: false,
+        secret: false,
+        shape: DoorShape::Arch,
+    }
+}
+
+impl PlacedRoom {
+    fn is_used(&self, side: &WallType) -> bool {
+        self.empty_side.contains(side) || self.doors.iter().any(|door| door.side == *side)
+    }
+
+    fn overlaps(&self, offset: Vec3, extension: Vec2) -> bool {
+        let reach = half_extents(self.extension) + half_extents(extension);
+        (self.offset.x - offset.x).abs() < reach.x
+            && (self.offset.z - offset.z).abs() < reach.y
+            && (self.offset.y - offset.y).abs() < GENERATED_WALL_HEIGHT
+    }
+}
+
+fn interior(extension: Vec2, margin: f32) -> Rect {
+    Rect::from_center_half_size(Vec2::ZERO, half_extents(extension) - Vec2::splat(margin))
+}
+
+// Puts a flight of stairs in the parent and a room on top of it whose floor
+// opening lines up with the hole the stairs climb through.
+fn stack_room(
+    rng: &mut ChaCha8Rng,
+    placed: &mut Vec<PlacedRoom>,
+    constraints: &DungeonConstraints,
+) -> bool {
+    let parent_index = rng.random_range(0..placed.len());
+    let parent = &placed[parent_index];
+    if parent.floor + 1 >= constraints.floors || !parent.stairs.is_empty() {
+        return false;
+    }
+
+    let direction = *WallType::all().choose(rng).unwrap();
+    let room_space = interior(parent.extension, WALL_THICKNESS / 2.0 + STAIR_WALL_MARGIN);
+    let Some(stairs) = [StairKind::Steps, StairKind::Ramp]
+        .into_iter()
+        .map(|kind| {
+            let mut stairs = StairDef {
+                kind,
+                start: (0.0, 0.0),
+                direction,
+                width: STAIR_WIDTH,
+                rise: storey_height(GENERATED_WALL_HEIGHT),
+            };
+            stairs.start = (-direction.normal().xz() * stairs.run() / 2.0).into();
+            stairs
+        })
+        .find(|stairs| {
+            let footprint = stairs.footprint();
+            room_space.contains(footprint.min) && room_space.contains(footprint.max)
+        })
+    else {
+        return false;
+    };
+
+    let hole = stairs.roof_opening();
+    let extension = random_extension(rng, constraints);
+    let above = interior(extension, WALL_THICKNESS / 2.0);
+    let offset = parent.offset + Vec3::Y * stairs.rise;
+    if !above.contains(hole.rect().min)
+        || !above.contains(hole.rect().max)
+        || placed.iter().any(|room| room.overlaps(offset, extension))
+    {
+        return false;
+    }
+
+    let depth = parent.depth + 1;
+    let floor = parent.floor + 1;
+    placed[parent_index].stairs.push(stairs);
+    placed[parent_index].openings.push(hole.clone());
+    placed.push(PlacedRoom {
+        extension,
+        offset,
+        depth,
+        floor,
+        empty_side: HashSet::new(),
+        doors: Vec::new(),
+        stairs: Vec::new(),
+        openings: vec![VerticalOpening {
+            surface: Surface::Floor,
+            ..hole
+        }],
+    });
+    true
+}
+
+fn extension_steps(constraints: &DungeonConstraints) -> (i32, i32) {
+    let min_step = (constraints.min_extension / ROOM_SIZE_STEP).ceil() as i32;
+    let max_step = (constraints.max_extension / ROOM_SIZE_STEP).floor() as i32;
+    (min_step, max_step)
+}
+
+fn random_extension(rng: &mut ChaCha8Rng, constraints: &DungeonConstraints) -> Vec2 {
+    let (min_step, max_step) = extension_steps(constraints);
+    let x = rng.random_range(min_step..=max_step) as f32 * ROOM_SIZE_STEP;
+    let z = rng.random_range(min_step..=max_step) as f32 * ROOM_SIZE_STEP;
+    Vec2::new(x, z)
+}
+
+fn validate_constraints(constraints: &DungeonConstraints) -> Result<(), GeneratorError> {
+    if constraints.room_count == 0 {
+        return Err(GeneratorError::NoRooms);
+    }
+    if constraints.floors == 0 {
+        return Err(GeneratorError::NoFloors);
+    }
+
+    let has_role = constraints
+        .role_weights
+        .iter()
+        .any(|(role, weight)| *weight > 0 && !matches!(role, RoomRole::Entry | RoomRole::Boss));
+    if !has_role {
+        return Err(GeneratorError::NoRoles);
+    }
+
+    let (min_step, max_step) = extension_steps(constraints);
+    let smallest = BASE_ROOM_SIZE + min_step as f32 * ROOM_SIZE_STEP;
+    if min_step > max_step || smallest <= GENERATED_DOOR_RADIUS {
+        return Err(GeneratorError::InvalidSizeRange {
+            min: constraints.min_extension,
+            max: constraints.max_extension,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn generate_dungeon(
+    seed: u64,
+    constraints: &DungeonConstraints,
+) -> Result<Vec<Room>, GeneratorError> {
+    validate_constraints(constraints)?;
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut placed = vec![PlacedRoom {
+        extension: random_extension(&mut rng, constraints),
+        offset: Vec3::ZERO,
+        depth: 0,
+        floor: 0,
+        empty_side: HashSet::new(),
+        doors: Vec::new(),
+        stairs: Vec::new(),
+        openings: Vec::new(),
+    }];
+
+    while placed.len() < constraints.room_count {
+        let mut attached = false;
+
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            if constraints.floors > 1 && rng.random_bool(constraints.stair_chance) {
+                if stack_room(&mut rng, &mut placed, constraints) {
+                    attached = true;
+                    break;
+                }
+                continue;
+            }
+
+            let parent_index = rng.random_range(0..placed.len());
+            let side = *WallType::all().choose(&mut rng).unwrap();
+            if placed[parent_index].is_used(&side) {
+                continue;
+            }
+
+            let extension = random_extension(&mut rng, constraints);
+            let parent = &placed[parent_index];
+            let distance = depth_towards(parent.extension, &side) + depth_towards(extension, &side);
+            let offset = parent.offset + side.normal() * distance;
+            if placed.iter().any(|room| room.overlaps(offset, extension)) {
+                continue;
+            }
+
+            // The narrower of the two walls is left open and the wider one
+            // gets the door, so the open side never looks out past the
+            // other room's wall.
+            let depth = parent.depth + 1;
+            let floor = parent.floor;
+            let facing = side.opposite();
+            let mut child = PlacedRoom {
+                extension,
+                offset,
+                depth,
+                floor,
+                empty_side: HashSet::new(),
+                doors: Vec::new(),
+                stairs: Vec::new(),
+                openings: Vec::new(),
+            };
+            if wall_length(extension, &facing) <= wall_length(parent.extension, &side) {
+                placed[parent_index].doors.push(generated_door(side));
+                child.empty_side.insert(facing);
+            } else {
+                placed[parent_index].empty_side.insert(side);
+                child.doors.push(generated_door(facing));
+            }
+            placed.push(child);
+            attached = true;
+            break;
+        }
+
+        if !attached {
+            return Err(GeneratorError::NoSpace {
+                placed: placed.len(),
+                requested: constraints.room_count,
+            });
+        }
+    }
+
+    let boss_index = placed
+        .iter()
+        .enumerate()
+        .skip(1)
+        .max_by_key(|(index, room)| (room.depth, usize::MAX - index))
+        .map(|(index, _)| index)
+        .filter(|_| constraints.boss_room);
+
+    let mut role_counts: HashMap<RoomRole, usize> = HashMap::new();
+    let rooms = placed
+        .into_iter()
+        .enumerate()
+        .map(|(index, room)| {
+            let role = if index == 0 {
+                RoomRole::Entry
+            } else if Some(index) == boss_index {
+                RoomRole::Boss
+            } else {
+                constraints
+                    .role_weights
+                    .iter()
+                    .filter(|(role, _)| !matches!(role, RoomRole::Entry | RoomRole::Boss))
+                    .collect::<Vec<_>>()
+                    .choose_weighted(&mut rng, |(_, weight)| *weight)
+                    .map(|(role, _)| *role)
+                    .unwrap()
+            };
+
+            let count = role_counts.entry(role).or_default();
+            *count += 1;
+            let scatter = role
+                .scatter()
+                .into_iter()
+                .map(|rule| ScatterRule {
+                    seed: rng.random(),
+                    ..rule
+                })
+                .collect();
+            let name = match role {
+                RoomRole::Entry | RoomRole::Boss => role.label().to_string(),
+                _ => format!("{} {}", role.label(), count),
+            };
+
+            Room {
+                name,
+                wall_height: GENERATED_WALL_HEIGHT,
+                extension_x: room.extension.x,
+                extension_z: room.extension.y,
+                empty_side: room.empty_side,
+                doors: room.doors,
+                offset: room.offset,
+                lighting: role.lighting(),
+                props: Vec::new(),
+                scatter,
+                stairs: room.stairs,
+                openings: room.openings,
+                theme: None,
+                wall_themes: HashMap::new(),
+            }
+        })
+        .collect();
+
+    Ok(rooms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::map::validation::validate_layout;
+
+    #[test]
+    fn same_seed_gives_same_layout() {
+        let constraints = DungeonConstraints::default();
+        for seed in 0..8 {
+            assert_eq!(
+                generate_dungeon(seed, &constraints).unwrap(),
+                generate_dungeon(seed, &constraints).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn generated_layouts_are_valid() {
+        let constraints = DungeonConstraints::default();
+        for seed in 0..64 {
+            let rooms = generate_dungeon(seed, &constraints).unwrap();
+            assert_eq!(rooms.len(), constraints.room_count, "seed {seed}");
+            let issues = validate_layout(&rooms, &[]);
+            assert!(issues.is_empty(), "seed {seed}: {issues:?}");
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{
+        map::{
+            CRUSHER_THICKNESS, HAZARD_PLATE_THICKNESS, KILL_PLANE_DAMAGE, SPIKE_EXTEND_TIME,
+            SPIKE_HEIGHT, SPIKE_RADIUS, SPIKE_SPACING,
+        },
+        player::PLAYER_SPAWN_POSITION,
+    },
+    entities::map::{
+        builder::{MapBuilder, merge_meshes},
+        trigger::{LastCheckpoint, TriggerActor, TriggerKind, TriggerVolume, spawn_trigger},
+    },
+    player::{
+        health::{DamageEvent, DamageType},
+        input::Motion,
+        player::Player,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum HazardKind {
+    Spikes {
+        damage: f32,
+        period: f32,
+        active: f32,
+        #[serde(default)]
+        phase: f32,
+    },
+    DamageFloor {
+        damage_per_second: f32,
+    },
+    Crusher {
+        damage: f32,
+        period: f32,
+        #[serde(default)]
+        phase: f32,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HazardDef {
+    pub name: String,
+    pub kind: HazardKind,
+    pub offset: (f32, f32, f32),
+    pub size: (f32, f32, f32),
+}
+
+#[derive(Component, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    half_size: Vec3,
+    clock: f32,
+    hit: HashSet<Entity>,
+}
+
+#[derive(Component)]
+pub struct HazardPart;
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct KillPlane(pub f32);
+
+#[derive(Resource, Default, Debug)]
+pub struct SafePosition(pub Option<Vec3>);
+
+struct HazardState {
+    armed: bool,
+    part_offset: f32,
+}
+
+impl Hazard {
+    fn state(&self) -> HazardState {
+        match self.kind {
+            HazardKind::Spikes {
+                period,
+                active,
+                phase,
+                ..
+            } => {
+                let time = (self.clock + phase).rem_euclid(period.max(f32::EPSILON));
+                let raised = if time < active {
+                    (time.min(active - time) / SPIKE_EXTEND_TIME).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                HazardState {
+                    armed: raised > 0.5,
+                    part_offset: -SPIKE_HEIGHT * (1.0 - raised),
+                }
+            }
+            HazardKind::DamageFloor { .. } => HazardState {
+                armed: true,
+                part_offset: HAZARD_PLATE_THICKNESS / 2.0 - self.half_size.y,
+            },
+            // Slams down in the first tenth of the cycle, rests, then winds
+            // back up slowly.
+            HazardKind::Crusher { period, phase, .. } => {
+                let cycle = (self.clock + phase).rem_euclid(period.max(f32::EPSILON)) / period;
+                let travel = self.half_size.y * 2.0 - CRUSHER_THICKNESS;
+                let lowered = if cycle < 0.1 {
+                    cycle / 0.1
+                } else if cycle < 0.3 {
+                    1.0
+                } else {
+                    1.0 - (cycle - 0.3) / 0.7
+                };
+                HazardState {
+                    armed: (0.1..0.3).contains(&cycle),
+                    part_offset: self.half_size.y - CRUSHER_THICKNESS / 2.0 - travel * lowered,
+                }
+            }
+        }
+    }
+}
+
+fn spike_mesh(half_size: Vec3) -> Option<Mesh> {
+    let columns = ((half_size.x * 2.0 / SPIKE_SPACING).floor() as usize).max(1);
+    let rows = ((half_size.z * 2.0 / SPIKE_SPACING).floor() as usize).max(1);
+    let origin = Vec3::new(
+        (columns - 1) as f32 * -SPIKE_SPACING / 2.0,
+        -half_size.y + SPIKE_HEIGHT / 2.0,
+        (rows - 1) as f32 * -SPIKE_SPACING / 2.0,
+    );
+
+    merge_meshes((0..columns).flat_map(|column| {
+        (0..rows).map(move |row| {
+            Mesh::from(Cone {
+                radius: SPIKE_RADIUS,
+                height: SPIKE_HEIGHT,
+            })
+            .translated_by(origin + Vec3::new(column as f32, 0.0, row as f32) * SPIKE_SPACING)
+        })
+    }))
+}
+
+pub fn spawn_level_hazards(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    builder: &mut MapBuilder,
+    defs: &[HazardDef],
+) {
+    for def in defs {
+        let (x, y, z) = def.offset;
+        let (width, height, depth) = def.size;
+        let half_size = Vec3::new(width, height, depth) / 2.0;
+
+        let (mesh, material) = match def.kind {
+            HazardKind::Spikes { .. } => {
+                let Some(mesh) = spike_mesh(half_size) else {
+                    continue;
+                };
+                (mesh, builder.materials.door.clone())
+            }
+            HazardKind::DamageFloor { .. } => (
+                Mesh::from(Cuboid::new(width, HAZARD_PLATE_THICKNESS, depth)),
+                builder.materials.hazard.clone(),
+            ),
+            HazardKind::Crusher { .. } => (
+                Mesh::from(Cuboid::new(width, CRUSHER_THICKNESS, depth)),
+                builder.materials.wall.clone(),
+            ),
+        };
+        let mesh = builder.add_mesh(meshes, mesh);
+        let hazard = Hazard {
+            kind: def.kind.clone(),
+            half_size,
+            clock: 0.0,
+            hit: HashSet::new(),
+        };
+
+        let part_offset = hazard.state().part_offset;
+
+        spawn_trigger(
+            commands,
+            builder,
+            TriggerVolume::new(
+                def.name.clone(),
+                TriggerKind::Trap,
+                vec![TriggerActor::Player, TriggerActor::Enemy],
+            ),
+            Vec3::new(x, y, z),
+            half_size,
+        )
+        .insert(hazard)
+        .with_children(|parent| {
+            parent.spawn((
+                HazardPart,
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_xyz(0.0, part_offset, 0.0),
+            ));
+        });
+    }
+}
+
+pub fn update_hazards(
+    time: Res<Time>,
+    mut hazards: Query<(Entity, &TriggerVolume, &mut Hazard, &Children)>,
+    mut parts: Query<&mut Transform, With<HazardPart>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let delta = time.delta_secs();
+    for (entity, volume, mut hazard, children) in &mut hazards {
+        hazard.clock += delta;
+        let state = hazard.state();
+
+        for child in children.iter() {
+            if let Ok(mut transform) = parts.get_mut(child) {
+                transform.translation.y = state.part_offset;
+            }
+        }
+
+        if !state.armed {
+            hazard.hit.clear();
+            continue;
+        }
+
+        for (actor, _) in volume.occupants() {
+            match hazard.kind {
+                HazardKind::DamageFloor { damage_per_second } => {
+                    damage.write(DamageEvent {
+                        target: actor,
+                        source: Some(entity),
+                        amount: damage_per_second * delta,
+                        kind: DamageType::Acid,
+                    });
+                }
+                HazardKind::Spikes { damage: amount, .. } => {
+                    if hazard.hit.insert(actor) {
+                        damage.write(DamageEvent {
+                            target: actor,
+                            source: Some(entity),
+                            amount,
+                            kind: DamageType::Pierce,
+                        });
+                    }
+                }
+                HazardKind::Crusher { damage: amount, .. } => {
+                    if hazard.hit.insert(actor) {
+                        damage.write(DamageEvent {
+                            target: actor,
+                            source: Some(entity),
+                            amount,
+                            kind: DamageType::Crush,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn track_safe_position(
+    player: Query<(Entity, &Transform, &KinematicCharacterControllerOutput), With<Player>>,
+    hazards: Query<&TriggerVolume, With<Hazard>>,
+    mut safe: ResMut<SafePosition>,
+) {
+    let Ok((entity, transform, output)) = player.single() else {
+        return;
+    };
+    if !output.grounded {
+        return;
+    }
+
+    let in_hazard = hazards
+        .iter()
+        .any(|volume| volume.occupants().any(|(actor, _)| actor == entity));
+    if !in_hazard {
+        safe.0 = Some(transform.translation);
+    }
+}
+
+// The sword shares the `Player` marker, so only the body with the
+// character controller is moved back.
+type PlayerBody = (With<Player>, With<KinematicCharacterController>);
+
+pub fn check_kill_plane(
+    mut player: Query<(Entity, &mut Transform, &mut Motion), PlayerBody>,
+    kill_plane: Res<KillPlane>,
+    safe: Res<SafePosition>,
+    checkpoint: Res<LastCheckpoint>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let Ok((entity, mut transform, mut motion)) = player.single_mut() else {
+        return;
+    };
+    if transform.translation.y >= kill_plane.0 {
+        return;
+    }
+
+    let respawn = safe.0.or(checkpoint.0).unwrap_or(PLAYER_SPAWN_POSITION);
+    info!("Player fell out of the map, respawning at {respawn}");
+    transform.translation = respawn;
+    motion.reset();
+    damage.write(DamageEvent {
+        target: entity,
+        source: None,
+        amount: KILL_PLANE_DAMAGE,
+        kind: DamageType::Fall,
+    });
+}
@@ -0,0 +1,371 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor},
+    ser::SerializeStruct,
+};
+use thiserror::Error;
+
+use crate::entities::map::{
+    hazard::HazardDef,
+    lighting::LightingProfile,
+    map::{DoorOpening, Room},
+    mechanism::MechanismDef,
+    platform::PlatformDef,
+    props::{PropDef, ScatterRule},
+    stairs::{StairDef, VerticalOpening},
+    theme::ThemeDef,
+    trigger::TriggerDef,
+    utility::WallType,
+    wall_mesh::DoorShape,
+};
+
+#[derive(Asset, TypePath, Clone, Debug, Deserialize, Serialize)]
+pub struct Level {
+    pub rooms: Vec<Room>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerDef>,
+    #[serde(default)]
+    pub hazards: Vec<HazardDef>,
+    #[serde(default)]
+    pub mechanisms: Vec<MechanismDef>,
+    #[serde(default)]
+    pub platforms: Vec<PlatformDef>,
+    #[serde(default)]
+    pub themes: Vec<ThemeDef>,
+}
+
+#[derive(Resource)]
+pub struct LevelHandle(pub Handle<Level>);
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read level file {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("{path}:{source}")]
+    Parse {
+        path: String,
+        source: ron::error::SpannedError,
+    },
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, LevelLoaderError> {
+        let path = load_context.path().display().to_string();
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|source| LevelLoaderError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        parse_level(&path, &bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LevelSaveError {
+    #[error("could not serialize level: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not write level file {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+pub fn save_level(path: &Path, level: &Level) -> Result<(), LevelSaveError> {
+    let text = ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::default())?;
+    std::fs::write(path, text + "\n").map_err(|source| LevelSaveError::Io {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+pub fn parse_level(path: &str, bytes: &[u8]) -> Result<Level, LevelLoaderError> {
+    ron::de::from_bytes(bytes).map_err(|source| LevelLoaderError::Parse {
+        path: path.to_string(),
+        source,
+    })
+}
+
+const ROOM_FIELDS: &[&str] = &[
+    "name",
+    "wall_height",
+    "door_radius",
+    "door_shape",
+    "extension",
+    "extension_x",
+    "extension_z",
+    "offset",
+    "empty_side",
+    "door_side",
+    "locked_doors",
+    "secret_walls",
+    "doors",
+    "lighting",
+    "props",
+    "scatter",
+    "stairs",
+    "openings",
+    "theme",
+    "wall_themes",
+];
+
+const UNNAMED_ROOM: &str = "<unnamed>";
+
+impl Serialize for Room {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let empty_side: Vec<WallType> = WallType::all()
+            .iter()
+            .copied()
+            .filter(|side| self.empty_side.contains(side))
+            .collect();
+
+        let len =
+            12 + usize::from(self.theme.is_some()) + usize::from(!self.wall_themes.is_empty());
+        let mut room = serializer.serialize_struct("Room", len)?;
+        room.serialize_field("name", &self.name)?;
+        room.serialize_field("wall_height", &self.wall_height)?;
+        room.serialize_field("extension_x", &self.extension_x)?;
+        room.serialize_field("extension_z", &self.extension_z)?;
+        room.serialize_field("offset", &(self.offset.x, self.offset.y, self.offset.z))?;
+        room.serialize_field("empty_side", &empty_side)?;
+        room.serialize_field("doors", &self.doors)?;
+        room.serialize_field("lighting", &self.lighting)?;
+        room.serialize_field("props", &self.props)?;
+        room.serialize_field("scatter", &self.scatter)?;
+        room.serialize_field("stairs", &self.stairs)?;
+        room.serialize_field("openings", &self.openings)?;
+        match &self.theme {
+            Some(theme) => room.serialize_field("theme", theme)?,
+            None => room.skip_field("theme")?,
+        }
+        if self.wall_themes.is_empty() {
+            room.skip_field("wall_themes")?;
+        } else {
+            let wall_themes: Vec<(WallType, &String)> = WallType::all()
+                .iter()
+                .filter_map(|side| Some((*side, self.wall_themes.get(side)?)))
+                .collect();
+            room.serialize_field("wall_themes", &wall_themes)?;
+        }
+        room.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Room {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Room", ROOM_FIELDS, RoomVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum RoomField {
+    Name,
+    WallHeight,
+    DoorRadius,
+    DoorShape,
+    Extension,
+    ExtensionX,
+    ExtensionZ,
+    Offset,
+    EmptySide,
+    DoorSide,
+    LockedDoors,
+    SecretWalls,
+    Doors,
+    Lighting,
+    Props,
+    Scatter,
+    Stairs,
+    Openings,
+    Theme,
+    WallThemes,
+}
+
+struct RoomVisitor;
+
+fn next_field<'de, A: MapAccess<'de>, T: Deserialize<'de>>(
+    map: &mut A,
+    room: &str,
+    field: &str,
+) -> Result<T, A::Error> {
+    map.next_value()
+        .map_err(|e| de::Error::custom(format!("room `{room}`, field `{field}`: {e}")))
+}
+
+fn missing_field<E: de::Error>(room: &str, field: &str) -> E {
+    E::custom(format!("room `{room}`: missing field `{field}`"))
+}
+
+impl<'de> Visitor<'de> for RoomVisitor {
+    type Value = Room;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a room definition")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Room, A::Error> {
+        let mut name: Option<String> = None;
+        let mut wall_height = None;
+        let mut door_radius: Option<f32> = None;
+        let mut door_shape: Option<DoorShape> = None;
+        let mut extension = None;
+        let mut extension_x = None;
+        let mut extension_z = None;
+        let mut offset: Option<(f32, f32, f32)> = None;
+        let mut empty_side: Option<HashSet<WallType>> = None;
+        let mut door_side: Option<HashSet<WallType>> = None;
+        let mut locked_doors: Option<HashSet<WallType>> = None;
+        let mut secret_walls: Option<HashSet<WallType>> = None;
+        let mut doors: Option<Vec<DoorOpening>> = None;
+        let mut lighting: Option<LightingProfile> = None;
+        let mut props: Option<Vec<PropDef>> = None;
+        let mut scatter: Option<Vec<ScatterRule>> = None;
+        let mut stairs: Option<Vec<StairDef>> = None;
+        let mut vertical_openings: Option<Vec<VerticalOpening>> = None;
+        let mut theme: Option<String> = None;
+        let mut wall_themes: Option<Vec<(WallType, String)>> = None;
+
+        loop {
+            let room = name.clone().unwrap_or_else(|| UNNAMED_ROOM.to_string());
+            let key = map
+                .next_key::<RoomField>()
+                .map_err(|e| de::Error::custom(format!("room `{room}`: {e}")))?;
+            let Some(key) = key else {
+                break;
+            };
+
+            match key {
+                RoomField::Name => name = Some(next_field(&mut map, &room, "name")?),
+                RoomField::WallHeight => {
+                    wall_height = Some(next_field(&mut map, &room, "wall_height")?)
+                }
+                RoomField::DoorRadius => {
+                    door_radius = Some(next_field(&mut map, &room, "door_radius")?)
+                }
+                RoomField::DoorShape => {
+                    door_shape = Some(next_field(&mut map, &room, "door_shape")?)
+                }
+                RoomField::Extension => extension = Some(next_field(&mut map, &room, "extension")?),
+                RoomField::ExtensionX => {
+                    extension_x = Some(next_field(&mut map, &room, "extension_x")?)
+                }
+                RoomField::ExtensionZ => {
+                    extension_z = Some(next_field(&mut map, &room, "extension_z")?)
+                }
+                RoomField::Offset => offset = Some(next_field(&mut map, &room, "offset")?),
+                RoomField::EmptySide => {
+                    empty_side = Some(next_field(&mut map, &room, "empty_side")?)
+                }
+                RoomField::DoorSide => door_side = Some(next_field(&mut map, &room, "door_side")?),
+                RoomField::LockedDoors => {
+                    locked_doors = Some(next_field(&mut map, &room, "locked_doors")?)
+                }
+                RoomField::SecretWalls => {
+                    secret_walls = Some(next_field(&mut map, &room, "secret_walls")?)
+                }
+                RoomField::Doors => doors = Some(next_field(&mut map, &room, "doors")?),
+                RoomField::Lighting => lighting = Some(next_field(&mut map, &room, "lighting")?),
+                RoomField::Props => props = Some(next_field(&mut map, &room, "props")?),
+                RoomField::Scatter => scatter = Some(next_field(&mut map, &room, "scatter")?),
+                RoomField::Stairs => stairs = Some(next_field(&mut map, &room, "stairs")?),
+                RoomField::Openings => {
+                    vertical_openings = Some(next_field(&mut map, &room, "openings")?)
+                }
+                RoomField::Theme => theme = Some(next_field(&mut map, &room, "theme")?),
+                RoomField::WallThemes => {
+                    wall_themes = Some(next_field(&mut map, &room, "wall_themes")?)
+                }
+            }
+        }
+
+        let name = name.ok_or_else(|| missing_field(UNNAMED_ROOM, "name"))?;
+        let (x, y, z) = offset.ok_or_else(|| missing_field(&name, "offset"))?;
+        let extension_x = extension_x
+            .or(extension)
+            .ok_or_else(|| missing_field(&name, "extension"))?;
+        let extension_z = extension_z
+            .or(extension)
+            .ok_or_else(|| missing_field(&name, "extension"))?;
+
+        let door_side = door_side.unwrap_or_default();
+        let locked_doors = locked_doors.unwrap_or_default();
+        let secret_walls = secret_walls.unwrap_or_default();
+        for (field, sides) in [
+            ("locked_doors", &locked_doors),
+            ("secret_walls", &secret_walls),
+        ] {
+            if let Some(side) = sides.iter().find(|side| !door_side.contains(side)) {
+                return Err(de::Error::custom(format!(
+                    "room `{name}`, field `{field}`: {side:?} is not listed in `door_side`"
+                )));
+            }
+        }
+
+        let mut openings = Vec::new();
+        if !door_side.is_empty() {
+            let door_radius = door_radius.ok_or_else(|| missing_field(&name, "door_radius"))?;
+            openings.extend(
+                WallType::all()
+                    .iter()
+                    .filter(|side| door_side.contains(side))
+                    .map(|side| DoorOpening {
+                        side: *side,
+                        position: 0.0,
+                        width: door_radius * 2.0,
+                        height: door_radius * 2.0,
+                        locked: locked_doors.contains(side),
+                        secret: secret_walls.contains(side),
+                        shape: door_shape.clone().unwrap_or_default(),
+                    }),
+            );
+        }
+        openings.extend(doors.unwrap_or_default());
+
+        Ok(Room {
+            wall_height: wall_height.ok_or_else(|| missing_field(&name, "wall_height"))?,
+            extension_x,
+            extension_z,
+            offset: Vec3::new(x, y, z),
+            empty_side: empty_side.unwrap_or_default(),
+            doors: openings,
+            lighting: lighting.unwrap_or_default(),
+            props: props.unwrap_or_default(),
+            scatter: scatter.unwrap_or_default(),
+            stairs: stairs.unwrap_or_default(),
+            openings: vertical_openings.unwrap_or_default(),
+            theme,
+            wall_themes: wall_themes.unwrap_or_default().into_iter().collect(),
+            name,
+        })
+    }
+}
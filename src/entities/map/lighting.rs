@@ -0,0 +1,231 @@
+use std::f32::consts::TAU;
+
+use bevy::{pbr::DistanceFog, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::map::{LIGHT_CULL_DISTANCE, TORCH_HEIGHT_RATIO, TORCH_WALL_OFFSET},
+    entities::map::{
+        builder::MapBuilder,
+        map::{Room, RoomBoundsData},
+        room_tracking::{CurrentRoom, RoomEntered, RoomId},
+        utility::WallType,
+    },
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum Flicker {
+    #[default]
+    Steady,
+    Candle,
+    Pulse,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct FogProfile {
+    pub color: (f32, f32, f32),
+    pub start: f32,
+    pub end: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LightingProfile {
+    pub ambient: f32,
+    pub torches_per_wall: u32,
+    pub torch_color: (f32, f32, f32),
+    pub torch_intensity: f32,
+    pub torch_range: f32,
+    pub fog: Option<FogProfile>,
+    pub flicker: Flicker,
+}
+
+impl Default for LightingProfile {
+    fn default() -> Self {
+        Self {
+            ambient: 40.0,
+            torches_per_wall: 2,
+            torch_color: (1.0, 0.62, 0.32),
+            torch_intensity: 150_000.0,
+            torch_range: 25.0,
+            fog: None,
+            flicker: Flicker::Candle,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct RoomLightingData(pub Vec<LightingProfile>);
+
+#[derive(Component)]
+pub struct RoomLights {
+    pub room: RoomId,
+}
+
+#[derive(Component)]
+pub struct Torch {
+    base_intensity: f32,
+    flicker: Flicker,
+    phase: f32,
+}
+
+impl Flicker {
+    pub fn factor(&self, t: f32) -> f32 {
+        match self {
+            Flicker::Steady => 1.0,
+            Flicker::Candle => {
+                let noise = (t * 7.3).sin() * 0.5 + (t * 13.1).sin() * 0.3 + (t * 23.7).sin() * 0.2;
+                0.85 + noise * 0.15
+            }
+            Flicker::Pulse => 0.7 + (t * 1.5).sin() * 0.3,
+        }
+    }
+}
+
+pub fn torch_positions(room: &Room, per_wall: u32) -> Vec<Vec3> {
+    let mut positions = Vec::new();
+    if per_wall == 0 {
+        return positions;
+    }
+
+    let height = room.wall_height * TORCH_HEIGHT_RATIO;
+    for side in WallType::all() {
+        if room.empty_side.contains(side) {
+            continue;
+        }
+
+        let half_length = room.wall_half_length(side);
+        let spacing = half_length * 2.0 / per_wall as f32;
+        let wall_center = room.wall_center(side) - room.offset;
+
+        for i in 0..per_wall {
+            let along = -half_length + spacing * (i as f32 + 0.5);
+            let blocked = room
+                .doors_on(side)
+                .any(|door| (along - door.position).abs() < door.width / 2.0 + TORCH_WALL_OFFSET);
+            if blocked {
+                continue;
+            }
+
+            positions.push(
+                wall_center - side.normal() * TORCH_WALL_OFFSET
+                    + side.along() * along
+                    + Vec3::Y * height,
+            );
+        }
+    }
+
+    positions
+}
+
+pub fn spawn_room_lights(
+    commands: &mut Commands,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+) {
+    let lighting = &room.lighting;
+    let (r, g, b) = lighting.torch_color;
+    let positions = torch_positions(room, lighting.torches_per_wall);
+
+    builder.stats.lights += positions.len();
+    builder
+        .spawn_in_room(
+            commands,
+            room_id,
+            (
+                RoomLights { room: room_id },
+                Transform::from_translation(room.offset),
+                Visibility::Hidden,
+            ),
+        )
+        .with_children(|parent| {
+            for (i, position) in positions.into_iter().enumerate() {
+                parent.spawn((
+                    PointLight {
+                        color: Color::srgb(r, g, b),
+                        intensity: lighting.torch_intensity,
+                        range: lighting.torch_range,
+                        shadows_enabled: false,
+                        ..default()
+                    },
+                    Transform::from_translation(position),
+                    Torch {
+                        base_intensity: lighting.torch_intensity,
+                        flicker: lighting.flicker,
+                        phase: i as f32 * TAU / 7.0,
+                    },
+                ));
+            }
+        });
+}
+
+pub fn apply_room_atmosphere(
+    mut commands: Commands,
+    mut entered: EventReader<RoomEntered>,
+    lighting: Res<RoomLightingData>,
+    mut ambient: ResMut<AmbientLight>,
+    cameras: Query<Entity, With<Camera3d>>,
+) {
+    let Some(event) = entered.read().last() else {
+        return;
+    };
+    let Some(profile) = lighting.0.get(event.room.0) else {
+        return;
+    };
+
+    ambient.brightness = profile.ambient;
+    for camera in &cameras {
+        match &profile.fog {
+            Some(fog) => {
+                let (r, g, b) = fog.color;
+                commands.entity(camera).insert(DistanceFog {
+                    color: Color::srgb(r, g, b),
+                    falloff: FogFalloff::Linear {
+                        start: fog.start,
+                        end: fog.end,
+                    },
+                    ..default()
+                });
+            }
+            None => {
+                commands.entity(camera).remove::<DistanceFog>();
+            }
+        }
+    }
+}
+
+pub fn cull_room_lights(
+    current_room: Res<CurrentRoom>,
+    bounds_data: Res<RoomBoundsData>,
+    mut room_lights: Query<(&RoomLights, &mut Visibility)>,
+) {
+    let Some(RoomId(current)) = current_room.0 else {
+        return;
+    };
+    let current_bounds = &bounds_data.bounds[current];
+
+    for (lights, mut visibility) in room_lights.iter_mut() {
+        let near =
+            bounds_data.bounds[lights.room.0].distance_to(current_bounds) <= LIGHT_CULL_DISTANCE;
+        visibility.set_if_neq(if near {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+pub fn flicker_torches(time: Res<Time>, mut torches: Query<(&Torch, &mut PointLight)>) {
+    let t = time.elapsed_secs();
+    for (torch, mut light) in torches.iter_mut() {
+        if torch.flicker == Flicker::Steady {
+            continue;
+        }
+        light.intensity = torch.base_intensity * torch.flicker.factor(t + torch.phase);
+    }
+}
+
+pub fn reset_atmosphere(mut ambient: ResMut<AmbientLight>) {
+    *ambient = AmbientLight::default();
+}
@@ -1,23 +1,67 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    constants::map::BASE_ROOM_SIZE,
-    entities::map::utility::{WallType, make_room},
+    constants::map::{
+        BASE_ROOM_SIZE, DEFAULT_LEVEL_PATH, ENTRY_ROOM_NAME, GROUND_HEIGHT, KILL_PLANE_DEPTH,
+        STREAMING_RADIUS,
+    },
+    entities::map::{
+        builder::{MapBuildStats, MapBuilder},
+        generator::{DungeonConstraints, DungeonSeed, generate_dungeon},
+        hazard::{KillPlane, SafePosition, spawn_level_hazards},
+        level::{Level, LevelHandle},
+        lighting::{LightingProfile, RoomLightingData},
+        mechanism::{Mechanisms, spawn_level_mechanisms},
+        platform::spawn_level_platforms,
+        props::{PropDef, ScatterRule},
+        room_tracking::RoomId,
+        secret::{SecretStats, count_secrets},
+        stairs::{StairDef, VerticalOpening},
+        streaming::LevelStreaming,
+        trigger::{TriggerActor, TriggerKind, TriggerVolume, spawn_level_triggers, spawn_trigger},
+        utility::{WallOrientation, WallType},
+        validation::validate_layout,
+        wall_mesh::DoorShape,
+    },
 };
 
 #[derive(Component)]
 pub struct MapEntity;
 
-struct Room {
-    name: String,
-    wall_height: f32,
-    door_radius: f32,
-    extension: f32,
-    empty_side: HashSet<WallType>,
-    door_side: HashSet<WallType>,
-    offset: Vec3,
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DoorOpening {
+    pub side: WallType,
+    #[serde(default)]
+    pub position: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub secret: bool,
+    #[serde(default)]
+    pub shape: DoorShape,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub wall_height: f32,
+    pub extension_x: f32,
+    pub extension_z: f32,
+    pub empty_side: HashSet<WallType>,
+    pub doors: Vec<DoorOpening>,
+    pub offset: Vec3,
+    pub lighting: LightingProfile,
+    pub props: Vec<PropDef>,
+    pub scatter: Vec<ScatterRule>,
+    pub stairs: Vec<StairDef>,
+    pub openings: Vec<VerticalOpening>,
+    pub theme: Option<String>,
+    pub wall_themes: HashMap<WallType, String>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,26 +77,75 @@ pub struct RoomBoundsData {
 }
 
 impl Room {
+    pub fn half_extents(&self) -> Vec2 {
+        Vec2::new(
+            BASE_ROOM_SIZE + self.extension_x,
+            BASE_ROOM_SIZE + self.extension_z,
+        )
+    }
+
+    pub fn wall_depth(&self, wall_type: &WallType) -> f32 {
+        match wall_type.orientation() {
+            WallOrientation::AlongX => self.half_extents().y,
+            WallOrientation::AlongZ => self.half_extents().x,
+        }
+    }
+
+    pub fn wall_half_length(&self, wall_type: &WallType) -> f32 {
+        match wall_type.orientation() {
+            WallOrientation::AlongX => self.half_extents().x,
+            WallOrientation::AlongZ => self.half_extents().y,
+        }
+    }
+
+    pub fn wall_center(&self, wall_type: &WallType) -> Vec3 {
+        self.offset + wall_type.normal() * self.wall_depth(wall_type)
+    }
+
+    pub fn door_center(&self, door: &DoorOpening) -> Vec3 {
+        self.wall_center(&door.side) + door.side.along() * door.position
+    }
+
+    pub fn doors_on<'a>(
+        &'a self,
+        wall_type: &'a WallType,
+    ) -> impl Iterator<Item = &'a DoorOpening> {
+        self.doors
+            .iter()
+            .filter(move |door| door.side == *wall_type)
+    }
+
+    pub fn has_door(&self, wall_type: &WallType) -> bool {
+        self.doors_on(wall_type).next().is_some()
+    }
+
+    pub fn wall_theme(&self, wall_type: &WallType) -> Option<&str> {
+        self.wall_themes
+            .get(wall_type)
+            .or(self.theme.as_ref())
+            .map(String::as_str)
+    }
+
     pub fn get_bounds(&self) -> (Vec3, Vec3) {
-        let map_size = BASE_ROOM_SIZE + self.extension;
+        let half_extents = self.half_extents();
 
         let min = Vec3::new(
-            self.offset.x - map_size,
-            self.offset.y - 5.0,
-            self.offset.z - map_size,
+            self.offset.x - half_extents.x,
+            self.offset.y - GROUND_HEIGHT * 2.0,
+            self.offset.z - half_extents.y,
         );
 
         let max = Vec3::new(
-            self.offset.x + map_size,
+            self.offset.x + half_extents.x,
             self.offset.y + self.wall_height,
-            self.offset.z + map_size,
+            self.offset.z + half_extents.y,
         );
 
         (min, max)
     }
 }
 
-fn extract_room_bounds(commands: &mut Commands, rooms: &Vec<Room>) {
+fn extract_room_bounds(commands: &mut Commands, rooms: &[Room]) {
     let bounds_vec: Vec<RoomBounds> = rooms
         .iter()
         .map(|room| {
@@ -69,164 +162,113 @@ fn extract_room_bounds(commands: &mut Commands, rooms: &Vec<Room>) {
     commands.insert_resource(RoomBoundsData { bounds: bounds_vec });
 }
 
+pub fn load_map(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    levels: &mut ResMut<Assets<Level>>,
+    seed: Option<&DungeonSeed>,
+) {
+    if let Some(DungeonSeed(seed)) = seed {
+        match generate_dungeon(*seed, &DungeonConstraints::default()) {
+            Ok(rooms) => {
+                info!("Generated dungeon from seed {seed}");
+                commands.insert_resource(LevelHandle(levels.add(Level {
+                    rooms,
+                    triggers: Vec::new(),
+                    hazards: Vec::new(),
+                    mechanisms: Vec::new(),
+                    platforms: Vec::new(),
+                    themes: Vec::new(),
+                })));
+                return;
+            }
+            Err(error) => error!("Could not generate dungeon from seed {seed}: {error}"),
+        }
+    }
+
+    commands.insert_resource(LevelHandle(asset_server.load(DEFAULT_LEVEL_PATH)));
+}
+
 pub fn setup_map(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     asset_server: &Res<AssetServer>,
+    level_handle: &LevelHandle,
+    level: &Level,
 ) {
-    let mut rooms: Vec<Room> = Vec::new();
-
-    rooms.push(Room {
-        name: String::from("Entry Area"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::EAST].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(0.0, 0.0, 0.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Tutorial Area"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 10.0,
-        empty_side: HashSet::new(),
-        door_side: [WallType::SOUTH, WallType::WEST].into_iter().collect(),
-        offset: Vec3::new(70.0, 0.0, 0.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Enemy Area 1"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::NORTH, WallType::SOUTH].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(70.0, 0.0, -70.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Enemy Area 2"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 10.0,
-        empty_side: HashSet::new(),
-        door_side: [WallType::NORTH, WallType::SOUTH].into_iter().collect(),
-        offset: Vec3::new(70.0, 0.0, -140.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Enemy Area 3"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 10.0,
-        empty_side: [WallType::NORTH].into_iter().collect(),
-        door_side: [WallType::EAST, WallType::WEST].into_iter().collect(),
-        offset: Vec3::new(70.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Puzzle Room 1"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::EAST].into_iter().collect(),
-        door_side: [WallType::WEST].into_iter().collect(),
-        offset: Vec3::new(0.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Reward Room 1"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: -10.0,
-        empty_side: [WallType::EAST].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(-50.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Enemy Room 4"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::WEST].into_iter().collect(),
-        door_side: [WallType::EAST].into_iter().collect(),
-        offset: Vec3::new(140.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Puzzle Room 2"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::WEST].into_iter().collect(),
-        door_side: [WallType::EAST, WallType::NORTH].into_iter().collect(),
-        offset: Vec3::new(200.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Hidden Room 1"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 0.0,
-        empty_side: [WallType::WEST].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(260.0, 0.0, -210.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Enemy Room 5"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 10.0,
-        empty_side: [WallType::SOUTH, WallType::NORTH].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(200.0, 0.0, -140.0),
-    });
-
-    rooms.push(Room {
-        name: String::from("Boss Room"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: 20.0,
-        empty_side: HashSet::new(),
-        door_side: [WallType::SOUTH, WallType::NORTH].into_iter().collect(),
-        offset: Vec3::new(200.0, 0.0, -50.0),
-    });
+    let rooms = &level.rooms;
+    if cfg!(debug_assertions) {
+        for issue in validate_layout(rooms, &level.mechanisms) {
+            warn!("Level layout: {issue}");
+        }
+    }
 
-    rooms.push(Room {
-        name: String::from("Final Reward Room"),
-        wall_height: 10.0,
-        door_radius: 3.0,
-        extension: -20.0,
-        empty_side: [WallType::SOUTH].into_iter().collect(),
-        door_side: HashSet::new(),
-        offset: Vec3::new(200.0, 0.0, 10.0),
-    });
+    extract_room_bounds(commands, rooms);
 
-    extract_room_bounds(commands, &rooms);
+    let mut builder = MapBuilder::new(materials, asset_server, &level.themes);
+    let mut streaming = LevelStreaming::new(level_handle.0.clone(), rooms);
+    let entry = rooms
+        .iter()
+        .position(|room| room.name == ENTRY_ROOM_NAME)
+        .unwrap_or_default();
+    for room_id in streaming.rooms_within(&[RoomId(entry)], STREAMING_RADIUS) {
+        streaming.load_room(commands, meshes, &mut builder, &rooms[room_id.0], room_id);
+    }
 
-    for room in rooms {
-        make_room(
+    for (index, room) in rooms.iter().enumerate() {
+        let (min, max) = room.get_bounds();
+        spawn_trigger(
             commands,
-            meshes,
-            materials,
-            room.wall_height,
-            room.door_radius,
-            room.offset,
-            room.extension,
-            room.empty_side,
-            room.door_side,
-            asset_server,
+            &mut builder,
+            TriggerVolume::new(
+                room.name.clone(),
+                TriggerKind::Room(RoomId(index)),
+                vec![TriggerActor::Player],
+            ),
+            (min + max) / 2.0,
+            (max - min) / 2.0,
         );
     }
+    spawn_level_triggers(commands, &mut builder, &level.triggers);
+    spawn_level_hazards(commands, meshes, &mut builder, &level.hazards);
+    let mechanisms = spawn_level_mechanisms(commands, meshes, &mut builder, &level.mechanisms);
+    commands.insert_resource(mechanisms);
+    spawn_level_platforms(commands, meshes, &mut builder, &level.platforms);
+
+    let lowest_floor = rooms.iter().map(|room| room.offset.y).fold(0.0, f32::min);
+    commands.insert_resource(KillPlane(lowest_floor - KILL_PLANE_DEPTH));
+    commands.insert_resource(SafePosition::default());
+    commands.insert_resource(SecretStats {
+        found: 0,
+        total: count_secrets(rooms),
+    });
+
+    let stats = builder.stats;
+    info!(
+        "Map built: {} entities, {} meshes, {} materials, {} lights",
+        stats.entities, stats.meshes, stats.materials, stats.lights
+    );
+    commands.insert_resource(stats);
+    commands.insert_resource(builder);
+    commands.insert_resource(streaming);
+    commands.insert_resource(RoomLightingData(
+        rooms.iter().map(|room| room.lighting.clone()).collect(),
+    ));
 }
 
 pub fn despawn_map(commands: &mut Commands, query: Query<Entity, With<MapEntity>>) {
     for entity in &query {
         commands.entity(entity).despawn();
     }
+    commands.remove_resource::<RoomBoundsData>();
+    commands.remove_resource::<MapBuildStats>();
+    commands.remove_resource::<MapBuilder>();
+    commands.remove_resource::<LevelStreaming>();
+    commands.remove_resource::<RoomLightingData>();
+    commands.remove_resource::<KillPlane>();
+    commands.remove_resource::<SafePosition>();
+    commands.remove_resource::<Mechanisms>();
+    commands.remove_resource::<SecretStats>();
+    commands.remove_resource::<LevelHandle>();
 }
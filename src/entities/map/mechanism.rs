@@ -0,0 +1,625 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy_rapier3d::control::KinematicCharacterController;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    constants::{
+        map::{LEVER_THROW, PLATE_PRESS_DEPTH, PLATE_THICKNESS, SWITCH_SIZE},
+        player::INTERACT_REACH,
+    },
+    entities::map::{
+        builder::MapBuilder,
+        door::{DoorAction, DoorCommand},
+        trigger::{
+            TriggerActor, TriggerEntered, TriggerExited, TriggerKind, TriggerVolume, spawn_trigger,
+        },
+        utility::WallType,
+    },
+    input::{Action, ActionState},
+    player::{player::Player, weapons::SwordStrike},
+};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum MechanismKind {
+    PressurePlate {
+        offset: (f32, f32, f32),
+        size: (f32, f32, f32),
+    },
+    Lever {
+        offset: (f32, f32, f32),
+    },
+    SwordSwitch {
+        offset: (f32, f32, f32),
+    },
+    And(Vec<String>),
+    Or(Vec<String>),
+    Sequence(Vec<String>),
+    Timer {
+        input: String,
+        duration: f32,
+    },
+    Door {
+        input: String,
+        room: String,
+        side: WallType,
+        #[serde(default)]
+        door: Option<usize>,
+    },
+    Light {
+        input: String,
+        offset: (f32, f32, f32),
+        #[serde(default = "default_light_color")]
+        color: (f32, f32, f32),
+        #[serde(default = "default_light_intensity")]
+        intensity: f32,
+    },
+}
+
+fn default_light_color() -> (f32, f32, f32) {
+    (0.6, 0.8, 1.0)
+}
+
+fn default_light_intensity() -> f32 {
+    200_000.0
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MechanismDef {
+    pub name: String,
+    pub kind: MechanismKind,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MechanismError {
+    #[error("mechanism `{0}` is defined more than once")]
+    DuplicateName(String),
+    #[error("mechanism `{node}` is wired to unknown mechanism `{input}`")]
+    UnknownInput { node: String, input: String },
+    #[error("mechanism `{0}` has no inputs")]
+    NoInputs(String),
+    #[error("mechanism `{0}` is part of a wiring loop")]
+    Cycle(String),
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct MechanismInput {
+    pub name: String,
+    pub active: bool,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct MechanismChanged {
+    pub name: String,
+    pub active: bool,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MechanismNode(pub usize);
+
+struct Node {
+    name: String,
+    kind: MechanismKind,
+    inputs: Vec<usize>,
+    active: bool,
+    progress: usize,
+    remaining: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Mechanisms {
+    nodes: Vec<Node>,
+    order: Vec<usize>,
+    index: HashMap<String, usize>,
+}
+
+impl MechanismKind {
+    pub fn is_input(&self) -> bool {
+        matches!(
+            self,
+            MechanismKind::PressurePlate { .. }
+                | MechanismKind::Lever { .. }
+                | MechanismKind::SwordSwitch { .. }
+        )
+    }
+
+    fn inputs(&self) -> Vec<&String> {
+        match self {
+            MechanismKind::And(inputs)
+            | MechanismKind::Or(inputs)
+            | MechanismKind::Sequence(inputs) => inputs.iter().collect(),
+            MechanismKind::Timer { input, .. }
+            | MechanismKind::Door { input, .. }
+            | MechanismKind::Light { input, .. } => vec![input],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Mechanisms {
+    pub fn new(defs: &[MechanismDef]) -> Result<Self, MechanismError> {
+        let mut index = HashMap::new();
+        for (i, def) in defs.iter().enumerate() {
+            if index.insert(def.name.clone(), i).is_some() {
+                return Err(MechanismError::DuplicateName(def.name.clone()));
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(defs.len());
+        for def in defs {
+            let names = def.kind.inputs();
+            if names.is_empty() && !def.kind.is_input() {
+                return Err(MechanismError::NoInputs(def.name.clone()));
+            }
+            let inputs = names
+                .into_iter()
+                .map(|input| {
+                    index
+                        .get(input)
+                        .copied()
+                        .ok_or_else(|| MechanismError::UnknownInput {
+                            node: def.name.clone(),
+                            input: input.clone(),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            nodes.push(Node {
+                name: def.name.clone(),
+                kind: def.kind.clone(),
+                inputs,
+                active: false,
+                progress: 0,
+                remaining: 0.0,
+            });
+        }
+
+        let mut pending: Vec<usize> = nodes.iter().map(|node| node.inputs.len()).collect();
+        let mut queue: VecDeque<usize> = (0..nodes.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(nodes.len());
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            for (j, node) in nodes.iter().enumerate() {
+                for _ in node.inputs.iter().filter(|&&input| input == i) {
+                    pending[j] -= 1;
+                    if pending[j] == 0 {
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        if let Some(stuck) = (0..nodes.len()).find(|&i| pending[i] > 0) {
+            return Err(MechanismError::Cycle(nodes[stuck].name.clone()));
+        }
+
+        Ok(Self {
+            nodes,
+            order,
+            index,
+        })
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.index.get(name).is_some_and(|&i| self.nodes[i].active)
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.nodes[node].name
+    }
+
+    // Applies input changes and advances timers by `delta`, returning every
+    // node whose output flipped.
+    pub fn step<'a>(
+        &mut self,
+        inputs: impl IntoIterator<Item = (&'a str, bool)>,
+        delta: f32,
+    ) -> Vec<usize> {
+        let before: Vec<bool> = self.nodes.iter().map(|node| node.active).collect();
+        for (name, active) in inputs {
+            match self.index.get(name) {
+                Some(&i) if self.nodes[i].kind.is_input() => self.nodes[i].active = active,
+                _ => warn!("Mechanism input `{name}` does not name an input"),
+            }
+        }
+
+        for &i in &self.order {
+            // Levers and switches are toggles, so every flip counts as a
+            // press; everything else presses on its rising edge.
+            let pressed = |input: usize, nodes: &[Node]| {
+                let toggle = matches!(
+                    nodes[input].kind,
+                    MechanismKind::Lever { .. } | MechanismKind::SwordSwitch { .. }
+                );
+                nodes[input].active != before[input] && (nodes[input].active || toggle)
+            };
+            let inputs = self.nodes[i].inputs.clone();
+            let active = match &self.nodes[i].kind {
+                kind if kind.is_input() => self.nodes[i].active,
+                MechanismKind::And(_) => inputs.iter().all(|&input| self.nodes[input].active),
+                MechanismKind::Or(_) => inputs.iter().any(|&input| self.nodes[input].active),
+                MechanismKind::Sequence(_) => {
+                    let mut progress = self.nodes[i].progress;
+                    for (position, &input) in inputs.iter().enumerate() {
+                        if progress == inputs.len() || !pressed(input, &self.nodes) {
+                            continue;
+                        }
+                        progress = if position == progress {
+                            progress + 1
+                        } else {
+                            usize::from(position == 0)
+                        };
+                    }
+                    self.nodes[i].progress = progress;
+                    progress == inputs.len()
+                }
+                MechanismKind::Timer { duration, .. } => {
+                    let remaining = if pressed(inputs[0], &self.nodes) {
+                        *duration
+                    } else {
+                        (self.nodes[i].remaining - delta).max(0.0)
+                    };
+                    self.nodes[i].remaining = remaining;
+                    remaining > 0.0
+                }
+                _ => self.nodes[inputs[0]].active,
+            };
+            self.nodes[i].active = active;
+        }
+
+        (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].active != before[i])
+            .collect()
+    }
+}
+
+fn spawn_part(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    builder: &mut MapBuilder,
+    node: usize,
+    mesh: Mesh,
+    transform: Transform,
+) {
+    let mesh = builder.add_mesh(meshes, mesh);
+    let material = builder.materials.door.clone();
+    builder.spawn(
+        commands,
+        (
+            MechanismNode(node),
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            transform,
+        ),
+    );
+}
+
+pub fn spawn_level_mechanisms(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    builder: &mut MapBuilder,
+    defs: &[MechanismDef],
+) -> Mechanisms {
+    let mechanisms = match Mechanisms::new(defs) {
+        Ok(mechanisms) => mechanisms,
+        Err(error) => {
+            error!("Level mechanisms disabled: {error}");
+            return Mechanisms::default();
+        }
+    };
+
+    for (node, def) in defs.iter().enumerate() {
+        match &def.kind {
+            MechanismKind::PressurePlate { offset, size } => {
+                let (x, y, z) = *offset;
+                let (width, height, depth) = *size;
+                let half_size = Vec3::new(width, height, depth) / 2.0;
+                spawn_trigger(
+                    commands,
+                    builder,
+                    TriggerVolume::new(
+                        def.name.clone(),
+                        TriggerKind::Mechanism(def.name.clone()),
+                        vec![TriggerActor::Player, TriggerActor::Prop],
+                    ),
+                    Vec3::new(x, y, z),
+                    half_size,
+                );
+                spawn_part(
+                    commands,
+                    meshes,
+                    builder,
+                    node,
+                    Mesh::from(Cuboid::new(width, PLATE_THICKNESS, depth)),
+                    Transform::from_xyz(x, y - half_size.y + PLATE_THICKNESS / 2.0, z),
+                );
+            }
+            MechanismKind::Lever { offset } => spawn_part(
+                commands,
+                meshes,
+                builder,
+                node,
+                Mesh::from(Cuboid::new(0.15, 1.0, 0.15)).translated_by(Vec3::Y * 0.5),
+                Transform::from_translation(Vec3::from(*offset))
+                    .with_rotation(Quat::from_rotation_x(-LEVER_THROW.to_radians())),
+            ),
+            MechanismKind::SwordSwitch { offset } => spawn_part(
+                commands,
+                meshes,
+                builder,
+                node,
+                Mesh::from(Cuboid::from_length(SWITCH_SIZE)),
+                Transform::from_translation(Vec3::from(*offset)),
+            ),
+            MechanismKind::Light {
+                offset,
+                color: (r, g, b),
+                intensity,
+                ..
+            } => {
+                builder.stats.lights += 1;
+                builder.spawn(
+                    commands,
+                    (
+                        MechanismNode(node),
+                        PointLight {
+                            color: Color::srgb(*r, *g, *b),
+                            intensity: *intensity,
+                            shadows_enabled: false,
+                            ..default()
+                        },
+                        Transform::from_translation(Vec3::from(*offset)),
+                        Visibility::Hidden,
+                    ),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    mechanisms
+}
+
+pub fn read_pressure_plates(
+    mut entered: EventReader<TriggerEntered>,
+    mut exited: EventReader<TriggerExited>,
+    volumes: Query<&TriggerVolume>,
+    mut inputs: EventWriter<MechanismInput>,
+) {
+    let triggers = entered
+        .read()
+        .map(|event| (event.trigger, &event.kind))
+        .chain(exited.read().map(|event| (event.trigger, &event.kind)));
+    for (trigger, kind) in triggers {
+        let TriggerKind::Mechanism(name) = kind else {
+            continue;
+        };
+        let Ok(volume) = volumes.get(trigger) else {
+            continue;
+        };
+        inputs.write(MechanismInput {
+            name: name.clone(),
+            active: volume.occupants().next().is_some(),
+        });
+    }
+}
+
+pub fn use_levers(
+    actions: Res<ActionState>,
+    player: Query<&Transform, (With<Player>, With<KinematicCharacterController>)>,
+    parts: Query<(&MechanismNode, &Transform)>,
+    mechanisms: Res<Mechanisms>,
+    mut inputs: EventWriter<MechanismInput>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+
+    let nearest = parts
+        .iter()
+        .filter(|(node, _)| matches!(mechanisms.nodes[node.0].kind, MechanismKind::Lever { .. }))
+        .map(|(node, transform)| (node, transform.translation.distance(player.translation)))
+        .filter(|(_, distance)| *distance <= INTERACT_REACH)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((node, _)) = nearest {
+        let name = mechanisms.name(node.0);
+        inputs.write(MechanismInput {
+            name: name.to_string(),
+            active: !mechanisms.is_active(name),
+        });
+    }
+}
+
+pub fn strike_sword_switches(
+    mut strikes: EventReader<SwordStrike>,
+    parts: Query<(&MechanismNode, &Transform)>,
+    mechanisms: Res<Mechanisms>,
+    mut inputs: EventWriter<MechanismInput>,
+) {
+    for strike in strikes.read() {
+        for (node, transform) in &parts {
+            let is_switch = matches!(
+                mechanisms.nodes[node.0].kind,
+                MechanismKind::SwordSwitch { .. }
+            );
+            if is_switch && strike.hits(transform.translation) {
+                let name = mechanisms.name(node.0);
+                inputs.write(MechanismInput {
+                    name: name.to_string(),
+                    active: !mechanisms.is_active(name),
+                });
+            }
+        }
+    }
+}
+
+pub fn evaluate_mechanisms(
+    time: Res<Time>,
+    mut inputs: EventReader<MechanismInput>,
+    mut mechanisms: ResMut<Mechanisms>,
+    mut changed: EventWriter<MechanismChanged>,
+    mut doors: EventWriter<DoorCommand>,
+) {
+    let inputs: Vec<MechanismInput> = inputs.read().cloned().collect();
+    let flipped = mechanisms.step(
+        inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.active)),
+        time.delta_secs(),
+    );
+
+    for node in flipped {
+        let node = &mechanisms.nodes[node];
+        debug!("Mechanism `{}` is now {}", node.name, node.active);
+        changed.write(MechanismChanged {
+            name: node.name.clone(),
+            active: node.active,
+        });
+
+        if let MechanismKind::Door {
+            room, side, door, ..
+        } = &node.kind
+        {
+            let actions = if node.active {
+                [DoorAction::Unlock, DoorAction::Open]
+            } else {
+                [DoorAction::Close, DoorAction::Lock]
+            };
+            doors.write_batch(actions.map(|action| DoorCommand {
+                room: room.clone(),
+                side: *side,
+                door: *door,
+                action,
+            }));
+        }
+    }
+}
+
+pub fn update_mechanism_parts(
+    mut changed: EventReader<MechanismChanged>,
+    mechanisms: Res<Mechanisms>,
+    mut parts: Query<(&MechanismNode, &mut Transform, Option<&mut Visibility>)>,
+) {
+    for event in changed.read() {
+        let Some(&index) = mechanisms.index.get(&event.name) else {
+            continue;
+        };
+        for (node, mut transform, visibility) in &mut parts {
+            if node.0 != index {
+                continue;
+            }
+            let direction = if event.active { 1.0 } else { -1.0 };
+            match &mechanisms.nodes[index].kind {
+                MechanismKind::PressurePlate { .. } => {
+                    transform.translation.y -= PLATE_PRESS_DEPTH * direction;
+                }
+                MechanismKind::Lever { .. } => {
+                    transform.rotation =
+                        Quat::from_rotation_x(LEVER_THROW.to_radians() * direction);
+                }
+                MechanismKind::SwordSwitch { .. } => {
+                    transform.rotation =
+                        Quat::from_rotation_y(std::f32::consts::FRAC_PI_4 * (direction + 1.0));
+                }
+                MechanismKind::Light { .. } => {
+                    if let Some(mut visibility) = visibility {
+                        *visibility = if event.active {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plate(name: &str) -> MechanismDef {
+        MechanismDef {
+            name: name.to_string(),
+            kind: MechanismKind::PressurePlate {
+                offset: (0.0, 0.0, 0.0),
+                size: (1.0, 0.2, 1.0),
+            },
+        }
+    }
+
+    fn gate(name: &str, kind: fn(Vec<String>) -> MechanismKind, inputs: &[&str]) -> MechanismDef {
+        MechanismDef {
+            name: name.to_string(),
+            kind: kind(inputs.iter().map(|input| input.to_string()).collect()),
+        }
+    }
+
+    // Steps on a plate and off it again.
+    fn press(mechanisms: &mut Mechanisms, name: &str) {
+        mechanisms.step([(name, true)], 0.0);
+        mechanisms.step([(name, false)], 0.0);
+    }
+
+    #[test]
+    fn and_needs_both_inputs() {
+        let mut mechanisms = Mechanisms::new(&[
+            plate("a"),
+            plate("b"),
+            gate("both", MechanismKind::And, &["a", "b"]),
+        ])
+        .unwrap();
+
+        mechanisms.step([("a", true)], 0.0);
+        assert!(!mechanisms.is_active("both"));
+        mechanisms.step([("b", true)], 0.0);
+        assert!(mechanisms.is_active("both"));
+        mechanisms.step([("a", false)], 0.0);
+        assert!(!mechanisms.is_active("both"));
+    }
+
+    #[test]
+    fn sequence_resets_on_a_wrong_input() {
+        let mut mechanisms = Mechanisms::new(&[
+            plate("a"),
+            plate("b"),
+            plate("c"),
+            gate("combo", MechanismKind::Sequence, &["a", "b", "c"]),
+        ])
+        .unwrap();
+
+        press(&mut mechanisms, "a");
+        press(&mut mechanisms, "c");
+        press(&mut mechanisms, "b");
+        press(&mut mechanisms, "c");
+        assert!(!mechanisms.is_active("combo"));
+
+        for name in ["a", "b", "c"] {
+            press(&mut mechanisms, name);
+        }
+        assert!(mechanisms.is_active("combo"));
+    }
+
+    #[test]
+    fn gates_follow_their_inputs_in_the_same_step() {
+        // Listed before the nodes they read from, so evaluation has to follow
+        // the wiring rather than the definition order.
+        let mut mechanisms = Mechanisms::new(&[
+            gate("any", MechanismKind::Or, &["both"]),
+            gate("both", MechanismKind::And, &["a", "b"]),
+            plate("a"),
+            plate("b"),
+        ])
+        .unwrap();
+
+        let flipped = mechanisms.step([("a", true), ("b", true)], 0.0);
+        let mut names: Vec<&str> = flipped.iter().map(|&node| mechanisms.name(node)).collect();
+        names.sort();
+        assert_eq!(names, ["a", "any", "b", "both"]);
+    }
+}
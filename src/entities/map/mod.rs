@@ -1,4 +1,48 @@
+pub mod builder;
+pub mod door;
+pub mod floor_plan;
+pub mod generator;
+pub mod hazard;
+pub mod level;
+pub mod lighting;
 pub mod map;
-mod utility;
+pub mod mechanism;
+pub mod platform;
+pub mod props;
+pub mod room_tracking;
+pub mod secret;
+pub mod stairs;
+pub mod streaming;
+pub mod theme;
+pub mod trigger;
+pub mod utility;
+pub mod validation;
+pub mod wall_mesh;
 
-pub use map::{despawn_map, setup_map};
+use bevy::prelude::*;
+
+use door::DoorCommand;
+use level::{Level, LevelLoader};
+use mechanism::{MechanismChanged, MechanismInput};
+use room_tracking::{CurrentRoom, RoomEntered, RoomExited};
+use secret::{Explosion, SecretDiscovered};
+use trigger::{LastCheckpoint, TriggerEntered, TriggerExited, TriggerStay};
+
+pub use map::{despawn_map, load_map, setup_map};
+
+pub fn map_plugin(app: &mut App) {
+    app.init_asset::<Level>()
+        .init_asset_loader::<LevelLoader>()
+        .init_resource::<CurrentRoom>()
+        .init_resource::<LastCheckpoint>()
+        .add_event::<DoorCommand>()
+        .add_event::<MechanismInput>()
+        .add_event::<MechanismChanged>()
+        .add_event::<RoomEntered>()
+        .add_event::<RoomExited>()
+        .add_event::<Explosion>()
+        .add_event::<SecretDiscovered>()
+        .add_event::<TriggerEntered>()
+        .add_event::<TriggerStay>()
+        .add_event::<TriggerExited>();
+}
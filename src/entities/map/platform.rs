@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{
+        map::{PLATFORM_RIDE_TOLERANCE, PLATFORM_SPEED},
+        player::{PLAYER_HALF_HEIGHT, PLAYER_RADIUS},
+    },
+    entities::map::{builder::MapBuilder, mechanism::Mechanisms},
+    player::player::Player,
+};
+
+fn default_platform_speed() -> f32 {
+    PLATFORM_SPEED
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PlatformDef {
+    pub name: String,
+    pub size: (f32, f32, f32),
+    pub waypoints: Vec<(f32, f32, f32)>,
+    #[serde(default = "default_platform_speed")]
+    pub speed: f32,
+    #[serde(default)]
+    pub pause: f32,
+    #[serde(default)]
+    pub looped: bool,
+    #[serde(default)]
+    pub mechanism: Option<String>,
+}
+
+#[derive(Component, Debug)]
+pub struct MovingPlatform {
+    pub name: String,
+    pub half_size: Vec3,
+    pub velocity: Vec3,
+    waypoints: Vec<Vec3>,
+    speed: f32,
+    pause: f32,
+    looped: bool,
+    mechanism: Option<String>,
+    from: usize,
+    to: usize,
+    wait: f32,
+    // Where physics has the platform after the last two fixed ticks; the
+    // transform is drawn in between.
+    position: Vec3,
+    previous: Vec3,
+}
+
+// Velocity handed to the player by the platform they stand on, and the part
+// of it they keep after jumping off.
+#[derive(Component, Default, Debug)]
+pub struct PlatformRider {
+    pub velocity: Option<Vec3>,
+    pub momentum: Vec3,
+}
+
+impl MovingPlatform {
+    fn next_stop(&self, goal: Option<usize>) -> Option<usize> {
+        let last = self.waypoints.len() - 1;
+        match goal {
+            Some(goal) if goal > self.to => Some(self.to + 1),
+            Some(goal) if goal < self.to => Some(self.to - 1),
+            Some(_) => None,
+            None if self.looped => Some((self.to + 1) % self.waypoints.len()),
+            None if self.to == last || (self.to > 0 && self.from > self.to) => {
+                Some(if self.to == 0 { 1 } else { self.to - 1 })
+            }
+            None => Some(self.to + 1),
+        }
+    }
+
+    // Moves the platform along its path, returning its new position. With a
+    // mechanism the platform travels to its last waypoint while the mechanism
+    // is active and back to its first one otherwise; without one it runs the
+    // path forever.
+    fn advance(&mut self, position: Vec3, active: Option<bool>, delta: f32) -> Vec3 {
+        if self.waypoints.len() < 2 {
+            return position;
+        }
+
+        let goal = active.map(|active| if active { self.waypoints.len() - 1 } else { 0 });
+        if let Some(goal) = goal {
+            let heading_away = (goal as isize - self.to as isize).signum()
+                == (self.from as isize - self.to as isize).signum();
+            if self.from != self.to && goal != self.to && heading_away {
+                std::mem::swap(&mut self.from, &mut self.to);
+                self.wait = 0.0;
+            }
+        }
+
+        if self.wait > 0.0 {
+            self.wait -= delta;
+            return position;
+        }
+
+        let mut position = position;
+        let mut travel = self.speed * delta;
+        for _ in 0..self.waypoints.len() {
+            let target = self.waypoints[self.to];
+            let distance = position.distance(target);
+            if distance > travel {
+                return position.move_towards(target, travel);
+            }
+
+            position = target;
+            travel -= distance;
+            let Some(next) = self.next_stop(goal) else {
+                self.from = self.to;
+                break;
+            };
+            self.from = self.to;
+            self.to = next;
+            if self.pause > 0.0 {
+                self.wait = self.pause;
+                break;
+            }
+        }
+        position
+    }
+
+    pub fn supports(&self, platform: Vec3, feet: Vec3) -> bool {
+        let top = platform.y + self.half_size.y;
+        let reach = self.half_size.xz() + Vec2::splat(PLAYER_RADIUS);
+        (feet.y - top).abs() <= PLATFORM_RIDE_TOLERANCE
+            && (feet.xz() - platform.xz()).abs().cmple(reach).all()
+    }
+}
+
+pub fn spawn_level_platforms(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    builder: &mut MapBuilder,
+    defs: &[PlatformDef],
+) {
+    for def in defs {
+        let Some(&start) = def.waypoints.first() else {
+            warn!("Platform `{}` has no waypoints", def.name);
+            continue;
+        };
+        let (width, height, depth) = def.size;
+        let half_size = Vec3::new(width, height, depth) / 2.0;
+        let mesh = builder.add_mesh(meshes, Mesh::from(Cuboid::new(width, height, depth)));
+        let material = builder.materials.wall.clone();
+
+        builder.spawn(
+            commands,
+            (
+                MovingPlatform {
+                    name: def.name.clone(),
+                    half_size,
+                    velocity: Vec3::ZERO,
+                    waypoints: def.waypoints.iter().copied().map(Vec3::from).collect(),
+                    speed: def.speed,
+                    pause: def.pause,
+                    looped: def.looped,
+                    mechanism: def.mechanism.clone(),
+                    from: 0,
+                    to: usize::from(def.waypoints.len() > 1),
+                    wait: def.pause,
+                    position: Vec3::from(start),
+                    previous: Vec3::from(start),
+                },
+                RigidBody::KinematicPositionBased,
+                Collider::cuboid(half_size.x, half_size.y, half_size.z),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(Vec3::from(start)),
+            ),
+        );
+    }
+}
+
+pub fn move_platforms(
+    time: Res<Time>,
+    mechanisms: Option<Res<Mechanisms>>,
+    mut platforms: Query<(&mut MovingPlatform, &mut Transform)>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+
+    for (mut platform, mut transform) in &mut platforms {
+        let active = platform.mechanism.as_ref().map(|name| {
+            mechanisms
+                .as_ref()
+                .is_some_and(|mechanisms| mechanisms.is_active(name))
+        });
+        let previous = platform.position;
+        let position = platform.advance(previous, active, delta);
+        platform.velocity = (position - previous) / delta;
+        platform.previous = previous;
+        platform.position = position;
+        transform.translation = position;
+    }
+}
+
+pub fn interpolate_platforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut platforms: Query<(&MovingPlatform, &mut Transform)>,
+) {
+    let fraction = fixed_time.overstep_fraction();
+    for (platform, mut transform) in &mut platforms {
+        transform.translation = platform.previous.lerp(platform.position, fraction);
+    }
+}
+
+pub fn ride_platforms(
+    mut player: Query<(&Transform, &mut PlatformRider), With<Player>>,
+    platforms: Query<(&MovingPlatform, &Transform), Without<Player>>,
+) {
+    let Ok((transform, mut rider)) = player.single_mut() else {
+        return;
+    };
+
+    let feet = transform.translation - Vec3::Y * PLAYER_HALF_HEIGHT;
+    rider.velocity = platforms
+        .iter()
+        .find(|(platform, platform_transform)| {
+            platform.supports(platform_transform.translation, feet)
+        })
+        .map(|(platform, _)| platform.velocity);
+}
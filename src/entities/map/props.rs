@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::map::{
+        MAX_SCATTER_ATTEMPTS, PROP_DOOR_CLEARANCE, PROP_MIN_SPACING, PROP_WALL_INSET,
+        WALL_THICKNESS,
+    },
+    entities::map::{
+        builder::MapBuilder,
+        map::Room,
+        room_tracking::RoomId,
+        stairs::{StairDef, Surface},
+        trigger::TriggerActor,
+        utility::WallType,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum PropShape {
+    Cuboid(f32, f32, f32),
+    Cylinder { half_height: f32, radius: f32 },
+    Ball(f32),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum PropBody {
+    #[default]
+    Static,
+    Dynamic,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PropDef {
+    pub scene: String,
+    #[serde(default)]
+    pub position: (f32, f32, f32),
+    #[serde(default)]
+    pub rotation: (f32, f32, f32),
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub collider: Option<PropShape>,
+    #[serde(default)]
+    pub body: PropBody,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ScatterPlacement {
+    #[default]
+    AlongWalls,
+    Anywhere,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ScatterRule {
+    pub prop: PropDef,
+    pub count: u32,
+    #[serde(default)]
+    pub placement: ScatterPlacement,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+// Dynamic props keep the transform they were pushed to when their room is
+// streamed out, looked up by their index in `room_props`.
+#[derive(Component)]
+pub struct Prop {
+    pub index: usize,
+    pub dynamic: bool,
+}
+
+impl PropShape {
+    // Models are authored with their origin on the floor, so the shape is
+    // lifted to rest on it.
+    fn collider(&self) -> Collider {
+        let (shape, lift) = match *self {
+            PropShape::Cuboid(x, y, z) => (Collider::cuboid(x, y, z), y),
+            PropShape::Cylinder {
+                half_height,
+                radius,
+            } => (Collider::cylinder(half_height, radius), half_height),
+            PropShape::Ball(radius) => (Collider::ball(radius), radius),
+        };
+        Collider::compound(vec![(Vec3::Y * lift, Quat::IDENTITY, shape)])
+    }
+}
+
+// FNV-1a, so scatter results only depend on the level data.
+fn name_seed(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn scatter_point(
+    rng: &mut ChaCha8Rng,
+    room: &Room,
+    placement: ScatterPlacement,
+) -> Option<(Vec3, f32)> {
+    let half_extents = room.half_extents() - Vec2::splat(WALL_THICKNESS / 2.0 + PROP_WALL_INSET);
+    if half_extents.min_element() <= 0.0 {
+        return None;
+    }
+
+    let yaw = rng.random_range(0.0..360.0);
+    match placement {
+        ScatterPlacement::Anywhere => {
+            let x = rng.random_range(-half_extents.x..=half_extents.x);
+            let z = rng.random_range(-half_extents.y..=half_extents.y);
+            Some((Vec3::new(x, 0.0, z), yaw))
+        }
+        ScatterPlacement::AlongWalls => {
+            let sides: Vec<&WallType> = WallType::all()
+                .iter()
+                .filter(|side| !room.empty_side.contains(side))
+                .collect();
+            if sides.is_empty() {
+                return None;
+            }
+
+            let side = sides[rng.random_range(0..sides.len())];
+            let normal = side.normal();
+            let depth = half_extents.dot(Vec2::new(normal.x, normal.z).abs());
+            let reach = half_extents.dot(Vec2::new(side.along().x, side.along().z));
+            let along = rng.random_range(-reach..=reach);
+            let near_door = room
+                .doors_on(side)
+                .any(|door| (along - door.position).abs() < door.width / 2.0 + PROP_DOOR_CLEARANCE);
+            if near_door {
+                return None;
+            }
+
+            Some((normal * depth + side.along() * along, yaw))
+        }
+    }
+}
+
+pub fn scatter_props(room: &Room, rule: &ScatterRule) -> Vec<PropDef> {
+    let mut rng = ChaCha8Rng::seed_from_u64(rule.seed ^ name_seed(&room.name));
+    let mut placed: Vec<Vec3> = Vec::new();
+    let blocked: Vec<Rect> = room
+        .stairs
+        .iter()
+        .map(StairDef::footprint)
+        .chain(room.openings_in(Surface::Floor))
+        .map(|rect| rect.inflate(PROP_MIN_SPACING / 2.0))
+        .collect();
+    let mut props = Vec::new();
+
+    for _ in 0..rule.count {
+        for _ in 0..MAX_SCATTER_ATTEMPTS {
+            let Some((position, yaw)) = scatter_point(&mut rng, room, rule.placement) else {
+                continue;
+            };
+            if blocked.iter().any(|rect| rect.contains(position.xz())) {
+                continue;
+            }
+            if placed
+                .iter()
+                .any(|other| other.distance(position) < PROP_MIN_SPACING)
+            {
+                continue;
+            }
+
+            placed.push(position);
+            let (_, y, _) = rule.prop.position;
+            props.push(PropDef {
+                position: (position.x, y, position.z),
+                rotation: (0.0, yaw, 0.0),
+                ..rule.prop.clone()
+            });
+            break;
+        }
+    }
+
+    props
+}
+
+pub fn room_props(room: &Room) -> Vec<PropDef> {
+    let mut props = room.props.clone();
+    for rule in &room.scatter {
+        props.extend(scatter_props(room, rule));
+    }
+    props
+}
+
+pub fn spawn_room_props(
+    commands: &mut Commands,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+    moved: &HashMap<usize, Transform>,
+) {
+    for (index, prop) in room_props(room).into_iter().enumerate() {
+        let (x, y, z) = prop.position;
+        let (pitch, yaw, roll) = prop.rotation;
+        let transform = Transform::from_translation(room.offset + Vec3::new(x, y, z))
+            .with_rotation(Quat::from_euler(
+                EulerRot::YXZ,
+                yaw.to_radians(),
+                pitch.to_radians(),
+                roll.to_radians(),
+            ))
+            .with_scale(Vec3::splat(prop.scale));
+        let transform = moved.get(&index).copied().unwrap_or(transform);
+
+        let scene = builder.load_scene(&prop.scene);
+        let dynamic = prop.collider.is_some() && prop.body == PropBody::Dynamic;
+        let mut entity = builder.spawn_in_room(
+            commands,
+            room_id,
+            (Prop { index, dynamic }, SceneRoot(scene), transform),
+        );
+
+        match (prop.collider, prop.body) {
+            (Some(shape), PropBody::Dynamic) => {
+                entity.insert((shape.collider(), RigidBody::Dynamic, TriggerActor::Prop));
+            }
+            (Some(shape), PropBody::Static) => {
+                entity.insert((shape.collider(), RigidBody::Fixed));
+            }
+            (None, PropBody::Dynamic) => {
+                warn!(
+                    "Prop `{}` in `{}` is dynamic but has no collider; spawning it static",
+                    prop.scene, room.name
+                );
+            }
+            (None, PropBody::Static) => {}
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::entities::map::{
+    map::{RoomBounds, RoomBoundsData},
+    trigger::{TriggerActor, TriggerKind, TriggerVolume},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RoomId(pub usize);
+
+#[derive(Resource, Default, Debug)]
+pub struct CurrentRoom(pub Option<RoomId>);
+
+#[derive(Event, Clone, Debug)]
+pub struct RoomEntered {
+    pub room: RoomId,
+    pub name: String,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RoomExited {
+    pub room: RoomId,
+    pub name: String,
+}
+
+impl RoomBounds {
+    pub fn volume(&self) -> f32 {
+        let size = self.max - self.min;
+        size.x * size.y * size.z
+    }
+
+    pub fn distance_to(&self, other: &RoomBounds) -> f32 {
+        let gap = (self.min - other.max)
+            .max(other.min - self.max)
+            .max(Vec3::ZERO);
+        gap.length()
+    }
+}
+
+impl RoomBoundsData {
+    // Where rooms overlap the player stays in the room they are already in;
+    // otherwise the smallest room wins, then the one declared first.
+    pub fn preferred_room(&self, rooms: &[RoomId], current: Option<RoomId>) -> Option<RoomId> {
+        if current.is_some_and(|room| rooms.contains(&room)) {
+            return current;
+        }
+
+        rooms.iter().copied().min_by(|&RoomId(a), &RoomId(b)| {
+            self.bounds[a]
+                .volume()
+                .total_cmp(&self.bounds[b].volume())
+                .then(a.cmp(&b))
+        })
+    }
+
+    pub fn name(&self, room: RoomId) -> &str {
+        &self.bounds[room.0].name
+    }
+}
+
+pub fn player_rooms(triggers: &Query<&TriggerVolume>) -> Vec<RoomId> {
+    triggers
+        .iter()
+        .filter(|volume| volume.contains_actor(TriggerActor::Player))
+        .filter_map(|volume| match volume.kind {
+            TriggerKind::Room(room) => Some(room),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn track_player_room(
+    triggers: Query<&TriggerVolume>,
+    bounds_data: Res<RoomBoundsData>,
+    mut current_room: ResMut<CurrentRoom>,
+    mut entered: EventWriter<RoomEntered>,
+    mut exited: EventWriter<RoomExited>,
+) {
+    let occupied = player_rooms(&triggers);
+    let room = bounds_data.preferred_room(&occupied, current_room.0);
+    if room == current_room.0 {
+        return;
+    }
+
+    if let Some(previous) = current_room.0 {
+        exited.write(RoomExited {
+            room: previous,
+            name: bounds_data.name(previous).to_string(),
+        });
+    }
+    if let Some(next) = room {
+        entered.write(RoomEntered {
+            room: next,
+            name: bounds_data.name(next).to_string(),
+        });
+    }
+
+    current_room.0 = room;
+}
+
+pub fn reset_current_room(mut current_room: ResMut<CurrentRoom>) {
+    current_room.0 = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_rapier3d::{prelude::CollisionEvent, rapier::geometry::CollisionEventFlags};
+
+    use super::*;
+    use crate::entities::map::trigger::{
+        TriggerEntered, TriggerExited, TriggerStay, detect_triggers,
+    };
+
+    fn bounds(name: &str, size: f32) -> RoomBounds {
+        RoomBounds {
+            name: name.to_string(),
+            min: Vec3::ZERO,
+            max: Vec3::splat(size),
+        }
+    }
+
+    fn rooms() -> RoomBoundsData {
+        RoomBoundsData {
+            bounds: vec![
+                bounds("Hall", 20.0),
+                bounds("Alcove", 5.0),
+                bounds("Nook", 5.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn player_stays_in_the_current_room_while_it_overlaps() {
+        let rooms = rooms();
+        let occupied = [RoomId(0), RoomId(1)];
+        assert_eq!(
+            rooms.preferred_room(&occupied, Some(RoomId(0))),
+            Some(RoomId(0))
+        );
+    }
+
+    #[test]
+    fn smallest_room_wins_an_overlap() {
+        let rooms = rooms();
+        let occupied = [RoomId(0), RoomId(1)];
+        assert_eq!(rooms.preferred_room(&occupied, None), Some(RoomId(1)));
+        assert_eq!(
+            rooms.preferred_room(&occupied, Some(RoomId(2))),
+            Some(RoomId(1))
+        );
+        assert_eq!(rooms.preferred_room(&[], Some(RoomId(0))), None);
+    }
+
+    #[test]
+    fn first_declared_room_breaks_a_tie() {
+        let rooms = rooms();
+        assert_eq!(
+            rooms.preferred_room(&[RoomId(2), RoomId(1)], None),
+            Some(RoomId(1))
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<String>);
+
+    fn record_room_events(
+        mut log: ResMut<Log>,
+        mut entered: EventReader<RoomEntered>,
+        mut exited: EventReader<RoomExited>,
+    ) {
+        for event in exited.read() {
+            log.0.push(format!("exit {}", event.name));
+        }
+        for event in entered.read() {
+            log.0.push(format!("enter {}", event.name));
+        }
+    }
+
+    #[test]
+    fn leaving_a_room_is_reported_before_entering_the_next() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<CollisionEvent>()
+            .add_event::<TriggerEntered>()
+            .add_event::<TriggerStay>()
+            .add_event::<TriggerExited>()
+            .add_event::<RoomEntered>()
+            .add_event::<RoomExited>()
+            .insert_resource(rooms())
+            .init_resource::<CurrentRoom>()
+            .init_resource::<Log>()
+            .add_systems(
+                Update,
+                (detect_triggers, track_player_room, record_room_events).chain(),
+            );
+
+        let world = app.world_mut();
+        let player = world.spawn(TriggerActor::Player).id();
+        let [hall, alcove] = [RoomId(0), RoomId(1)].map(|room| {
+            world
+                .spawn(TriggerVolume::new(
+                    "",
+                    TriggerKind::Room(room),
+                    vec![TriggerActor::Player],
+                ))
+                .id()
+        });
+
+        let mut step = |event: fn(Entity, Entity, CollisionEventFlags) -> CollisionEvent,
+                        trigger: Entity| {
+            app.world_mut()
+                .send_event(event(trigger, player, CollisionEventFlags::SENSOR));
+            app.update();
+            std::mem::take(&mut app.world_mut().resource_mut::<Log>().0)
+        };
+
+        assert_eq!(step(CollisionEvent::Started, hall), ["enter Hall"]);
+        assert_eq!(step(CollisionEvent::Started, alcove), Vec::<String>::new());
+        assert_eq!(
+            step(CollisionEvent::Stopped, hall),
+            ["exit Hall", "enter Alcove"]
+        );
+        assert_eq!(step(CollisionEvent::Stopped, alcove), ["exit Alcove"]);
+        assert_eq!(app.world().resource::<CurrentRoom>().0, None);
+    }
+}
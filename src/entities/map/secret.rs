@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    constants::map::{
+        FRAGMENT_GRID, FRAGMENT_LIFETIME, FRAGMENT_SPEED, SECRET_WALL_HITS, WALL_THICKNESS,
+    },
+    entities::map::{
+        builder::{MapBuilder, tiled_box},
+        map::Room,
+        room_tracking::RoomId,
+        streaming::LevelStreaming,
+        utility::WallType,
+        wall_mesh::WallMeshBuilder,
+    },
+    player::weapons::SwordStrike,
+};
+
+#[derive(Component, Debug)]
+pub struct SecretWall {
+    pub room: String,
+    pub index: usize,
+    pub side: WallType,
+    room_id: RoomId,
+    half_size: Vec3,
+    hits: u32,
+}
+
+#[derive(Component)]
+pub struct WallFragment {
+    remaining: f32,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Explosion {
+    pub origin: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct SecretDiscovered {
+    pub room: RoomId,
+    pub name: String,
+    pub wall: usize,
+    pub side: WallType,
+}
+
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct SecretStats {
+    pub found: usize,
+    pub total: usize,
+}
+
+impl SecretWall {
+    fn closest_point(&self, center: Vec3, point: Vec3) -> Vec3 {
+        point.clamp(center - self.half_size, center + self.half_size)
+    }
+}
+
+pub fn count_secrets(rooms: &[Room]) -> usize {
+    rooms
+        .iter()
+        .flat_map(|room| &room.doors)
+        .filter(|door| door.secret)
+        .count()
+}
+
+// Fills secret openings with a brick panel in the shape of the opening, so it
+// looks like the rest of the wall until it is broken.
+pub fn spawn_secret_walls(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+    broken: &HashSet<usize>,
+) {
+    for (index, opening) in room.doors.iter().enumerate() {
+        if !opening.secret || broken.contains(&index) || room.empty_side.contains(&opening.side) {
+            continue;
+        }
+
+        let size = Vec3::new(opening.width, opening.height, WALL_THICKNESS);
+        let mut panel = WallMeshBuilder::default();
+        panel.add_panel(&opening.shape, size);
+        let Some((mesh, collider)) = panel.build() else {
+            continue;
+        };
+        let mesh = builder.add_mesh(meshes, mesh);
+        let rotation = Quat::from_rotation_arc(Vec3::Z, opening.side.normal());
+        let material = builder.theme(room.wall_theme(&opening.side)).wall;
+        let half_size = size / 2.0;
+
+        builder.spawn_in_room(
+            commands,
+            room_id,
+            (
+                SecretWall {
+                    room: room.name.clone(),
+                    index,
+                    side: opening.side,
+                    room_id,
+                    half_size: (rotation * half_size).abs(),
+                    hits: SECRET_WALL_HITS,
+                },
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(
+                    room.door_center(opening) + Vec3::Y * opening.height / 2.0,
+                )
+                .with_rotation(rotation),
+                collider,
+            ),
+        );
+    }
+}
+
+fn spawn_fragments(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    builder: &mut MapBuilder,
+    wall: &SecretWall,
+    transform: &Transform,
+    material: &Handle<StandardMaterial>,
+    push: Vec3,
+) {
+    let local_half = transform.rotation.inverse() * wall.half_size;
+    let size = local_half.abs() * 2.0;
+    let piece = Vec3::new(
+        size.x / FRAGMENT_GRID as f32,
+        size.y / FRAGMENT_GRID as f32,
+        size.z,
+    );
+    let mesh = builder.cached_mesh(meshes, "wall_fragment", piece, || {
+        tiled_box(Vec3::ZERO, piece)
+    });
+    let half_piece = piece / 2.0;
+
+    for column in 0..FRAGMENT_GRID {
+        for row in 0..FRAGMENT_GRID {
+            let local = Vec3::new(
+                (column as f32 + 0.5) * piece.x - size.x / 2.0,
+                (row as f32 + 0.5) * piece.y - size.y / 2.0,
+                0.0,
+            );
+            let position = transform.transform_point(local);
+            let spread = (position - transform.translation).normalize_or_zero();
+            builder.spawn_in_room(
+                commands,
+                wall.room_id,
+                (
+                    WallFragment {
+                        remaining: FRAGMENT_LIFETIME,
+                    },
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_translation(position).with_rotation(transform.rotation),
+                    RigidBody::Dynamic,
+                    Collider::cuboid(half_piece.x, half_piece.y, half_piece.z),
+                    Velocity::linear((push + spread * 0.5) * FRAGMENT_SPEED),
+                ),
+            );
+        }
+    }
+}
+
+pub fn break_secret_walls(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut builder: ResMut<MapBuilder>,
+    mut strikes: EventReader<SwordStrike>,
+    mut explosions: EventReader<Explosion>,
+    mut walls: Query<(
+        Entity,
+        &mut SecretWall,
+        &Transform,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut discovered: EventWriter<SecretDiscovered>,
+) {
+    let strikes: Vec<SwordStrike> = strikes.read().copied().collect();
+    let explosions: Vec<Explosion> = explosions.read().copied().collect();
+    if strikes.is_empty() && explosions.is_empty() {
+        return;
+    }
+
+    for (entity, mut wall, transform, material) in &mut walls {
+        let center = transform.translation;
+        let mut push = None;
+        for strike in &strikes {
+            if strike.hits(wall.closest_point(center, strike.origin)) {
+                wall.hits = wall.hits.saturating_sub(1);
+                push = Some(strike.direction);
+            }
+        }
+        for explosion in &explosions {
+            if wall
+                .closest_point(center, explosion.origin)
+                .distance(explosion.origin)
+                <= explosion.radius
+            {
+                wall.hits = 0;
+                push = Some((center - explosion.origin).normalize_or_zero());
+            }
+        }
+
+        let Some(push) = push else {
+            continue;
+        };
+        if wall.hits > 0 {
+            continue;
+        }
+
+        info!("Secret wall in `{}` broken", wall.room);
+        commands.entity(entity).despawn();
+        spawn_fragments(
+            &mut commands,
+            &mut meshes,
+            &mut builder,
+            &wall,
+            transform,
+            &material.0,
+            push,
+        );
+        discovered.write(SecretDiscovered {
+            room: wall.room_id,
+            name: wall.room.clone(),
+            wall: wall.index,
+            side: wall.side,
+        });
+    }
+}
+
+pub fn record_secrets(
+    mut discovered: EventReader<SecretDiscovered>,
+    mut stats: ResMut<SecretStats>,
+    mut streaming: ResMut<LevelStreaming>,
+) {
+    for event in discovered.read() {
+        streaming.break_wall(event.room, event.wall);
+        stats.found += 1;
+    }
+}
+
+pub fn expire_wall_fragments(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fragments: Query<(Entity, &mut WallFragment)>,
+) {
+    for (entity, mut fragment) in &mut fragments {
+        fragment.remaining -= time.delta_secs();
+        if fragment.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
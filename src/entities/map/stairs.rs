@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::map::{
+        GROUND_HEIGHT, RAMP_ANGLE, ROOF_THICKNESS, STAIR_HEADROOM, STAIR_SLAB_THICKNESS,
+        STAIR_STEP_DEPTH, STAIR_STEP_HEIGHT,
+    },
+    entities::map::{
+        builder::{MapBuilder, merge_meshes, tiled_box},
+        map::Room,
+        room_tracking::RoomId,
+        utility::WallType,
+    },
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Surface {
+    Floor,
+    Roof,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct VerticalOpening {
+    pub surface: Surface,
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum StairKind {
+    #[default]
+    Steps,
+    Ramp,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct StairDef {
+    #[serde(default)]
+    pub kind: StairKind,
+    pub start: (f32, f32),
+    pub direction: WallType,
+    pub width: f32,
+    pub rise: f32,
+}
+
+pub struct StairPiece {
+    pub center: Vec3,
+    pub rotation: Quat,
+    pub size: Vec3,
+}
+
+impl VerticalOpening {
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.position.into(), self.size.into())
+    }
+}
+
+// Height between a room's floor and the floor of a room stacked on top.
+pub fn storey_height(wall_height: f32) -> f32 {
+    wall_height + ROOF_THICKNESS + GROUND_HEIGHT * 2.0
+}
+
+impl Room {
+    pub fn storey_height(&self) -> f32 {
+        storey_height(self.wall_height)
+    }
+
+    pub fn openings_in(&self, surface: Surface) -> impl Iterator<Item = Rect> + '_ {
+        self.openings
+            .iter()
+            .filter(move |opening| opening.surface == surface)
+            .map(VerticalOpening::rect)
+    }
+}
+
+impl StairDef {
+    pub fn step_count(&self) -> usize {
+        (self.rise / STAIR_STEP_HEIGHT).ceil().max(1.0) as usize
+    }
+
+    pub fn run(&self) -> f32 {
+        match self.kind {
+            StairKind::Steps => self.step_count() as f32 * STAIR_STEP_DEPTH,
+            StairKind::Ramp => self.rise / RAMP_ANGLE.to_radians().tan(),
+        }
+    }
+
+    fn span(&self, from: f32, to: f32) -> Rect {
+        let start = Vec2::from(self.start);
+        let forward = self.direction.normal().xz();
+        let across = self.direction.along().xz() * self.width / 2.0;
+        Rect::from_corners(
+            start + forward * from - across,
+            start + forward * to + across,
+        )
+    }
+
+    pub fn footprint(&self) -> Rect {
+        self.span(0.0, self.run())
+    }
+
+    // The part of the roof above the top of the flight that has to be cut
+    // away so the player keeps their head while climbing through.
+    pub fn roof_opening(&self) -> VerticalOpening {
+        let run = self.run();
+        let clearance = (STAIR_HEADROOM * run / self.rise).min(run);
+        let rect = self.span(run - clearance, run);
+        VerticalOpening {
+            surface: Surface::Roof,
+            position: rect.center().into(),
+            size: rect.size().into(),
+        }
+    }
+
+    pub fn pieces(&self) -> Vec<StairPiece> {
+        let start = Vec3::new(self.start.0, 0.0, self.start.1);
+        let forward = self.direction.normal();
+        let across = self.direction.along();
+
+        match self.kind {
+            StairKind::Steps => {
+                let count = self.step_count();
+                let step_height = self.rise / count as f32;
+                (0..count)
+                    .map(|step| {
+                        let height = step_height * (step + 1) as f32;
+                        StairPiece {
+                            center: start
+                                + forward * STAIR_STEP_DEPTH * (step as f32 + 0.5)
+                                + Vec3::Y * height / 2.0,
+                            rotation: Quat::IDENTITY,
+                            size: forward.abs() * STAIR_STEP_DEPTH
+                                + across * self.width
+                                + Vec3::Y * height,
+                        }
+                    })
+                    .collect()
+            }
+            StairKind::Ramp => {
+                let run = self.run();
+                let rotation = Quat::from_axis_angle(forward.cross(Vec3::Y), self.rise.atan2(run));
+                let top_center = start + forward * run / 2.0 + Vec3::Y * self.rise / 2.0;
+                vec![StairPiece {
+                    center: top_center - rotation * Vec3::Y * STAIR_SLAB_THICKNESS / 2.0,
+                    rotation,
+                    size: forward.abs() * run.hypot(self.rise)
+                        + across * self.width
+                        + Vec3::Y * STAIR_SLAB_THICKNESS,
+                }]
+            }
+        }
+    }
+}
+
+pub fn spawn_stairs(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+) {
+    let pieces: Vec<StairPiece> = room.stairs.iter().flat_map(StairDef::pieces).collect();
+    let Some(mesh) = merge_meshes(pieces.iter().map(|piece| {
+        tiled_box(Vec3::ZERO, piece.size)
+            .rotated_by(piece.rotation)
+            .translated_by(piece.center)
+    })) else {
+        return;
+    };
+
+    let collider = Collider::compound(
+        pieces
+            .iter()
+            .map(|piece| {
+                let half_size = piece.size / 2.0;
+                (
+                    piece.center,
+                    piece.rotation,
+                    Collider::cuboid(half_size.x, half_size.y, half_size.z),
+                )
+            })
+            .collect(),
+    );
+
+    let mesh = builder.add_mesh(meshes, mesh);
+    let material = builder.theme(room.theme.as_deref()).wall;
+    builder.spawn_in_room(
+        commands,
+        room_id,
+        (
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::from_translation(room.offset),
+            GlobalTransform::default(),
+            collider,
+        ),
+    );
+}
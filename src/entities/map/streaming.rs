@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::map::STREAMING_RADIUS,
+    entities::map::{
+        builder::MapBuilder,
+        door::{Door, DoorCommand, DoorState, spawn_room_doors},
+        level::Level,
+        lighting::spawn_room_lights,
+        map::Room,
+        props::{Prop, spawn_room_props},
+        room_tracking::{RoomId, player_rooms},
+        secret::spawn_secret_walls,
+        trigger::TriggerVolume,
+        utility::make_room,
+        validation::room_graph,
+    },
+};
+
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RoomMember(pub RoomId);
+
+#[derive(Default, Debug)]
+pub struct RoomState {
+    pub doors: HashMap<usize, DoorState>,
+    pub broken_walls: HashSet<usize>,
+    pub props: HashMap<usize, Transform>,
+}
+
+#[derive(Resource)]
+pub struct LevelStreaming {
+    level: Handle<Level>,
+    graph: Vec<Vec<usize>>,
+    names: Vec<String>,
+    loaded: HashSet<RoomId>,
+    states: HashMap<RoomId, RoomState>,
+    pending: Vec<DoorCommand>,
+}
+
+impl LevelStreaming {
+    pub fn new(level: Handle<Level>, rooms: &[Room]) -> Self {
+        Self {
+            level,
+            graph: room_graph(rooms),
+            names: rooms.iter().map(|room| room.name.clone()).collect(),
+            loaded: HashSet::new(),
+            states: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn is_loaded(&self, room: RoomId) -> bool {
+        self.loaded.contains(&room)
+    }
+
+    pub fn defer_door_command(&mut self, command: &DoorCommand) -> bool {
+        let unloaded = self
+            .names
+            .iter()
+            .enumerate()
+            .any(|(index, name)| *name == command.room && !self.is_loaded(RoomId(index)));
+        if unloaded {
+            self.pending.push(command.clone());
+        }
+        unloaded
+    }
+
+    pub fn break_wall(&mut self, room: RoomId, index: usize) {
+        self.states
+            .entry(room)
+            .or_default()
+            .broken_walls
+            .insert(index);
+    }
+
+    pub fn rooms_within(&self, origins: &[RoomId], radius: usize) -> HashSet<RoomId> {
+        let mut reached: HashSet<RoomId> = origins.iter().copied().collect();
+        let mut queue: VecDeque<(RoomId, usize)> = origins.iter().map(|&room| (room, 0)).collect();
+
+        while let Some((RoomId(index), distance)) = queue.pop_front() {
+            if distance == radius {
+                continue;
+            }
+            for &next in self.graph.get(index).into_iter().flatten() {
+                if reached.insert(RoomId(next)) {
+                    queue.push_back((RoomId(next), distance + 1));
+                }
+            }
+        }
+
+        reached
+    }
+
+    pub fn load_room(
+        &mut self,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        builder: &mut MapBuilder,
+        room: &Room,
+        room_id: RoomId,
+    ) {
+        if !self.loaded.insert(room_id) {
+            return;
+        }
+
+        let state = self.states.entry(room_id).or_default();
+        let (ready, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|command| command.room == room.name);
+        self.pending = pending;
+
+        for command in ready {
+            for (index, opening) in room.doors.iter().enumerate() {
+                if !command.targets(opening.side, index) {
+                    continue;
+                }
+                let current = state
+                    .doors
+                    .get(&index)
+                    .copied()
+                    .unwrap_or(DoorState::initial(opening));
+                if let Some(next) = current.after(command.action) {
+                    state.doors.insert(index, next);
+                }
+            }
+        }
+
+        make_room(commands, meshes, builder, room, room_id);
+        spawn_room_lights(commands, builder, room, room_id);
+        spawn_room_props(commands, builder, room, room_id, &state.props);
+        spawn_room_doors(commands, meshes, builder, room, room_id, &state.doors);
+        spawn_secret_walls(
+            commands,
+            meshes,
+            builder,
+            room,
+            room_id,
+            &state.broken_walls,
+        );
+    }
+
+    // Builds a room again after its definition changed. The caller despawns
+    // the entities of the old room first.
+    pub fn reload_room(
+        &mut self,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        builder: &mut MapBuilder,
+        room: &Room,
+        room_id: RoomId,
+    ) {
+        // The edit may have added or removed doors, so saved state keyed by
+        // door index no longer lines up.
+        self.loaded.remove(&room_id);
+        self.states.remove(&room_id);
+        self.load_room(commands, meshes, builder, room, room_id);
+    }
+}
+
+// Doors and dynamic props carry state that outlives their room being
+// streamed out.
+type StreamedEntity = (
+    Entity,
+    &'static RoomMember,
+    Option<&'static Door>,
+    Option<(&'static Prop, &'static Transform)>,
+);
+
+pub fn stream_rooms(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut builder: ResMut<MapBuilder>,
+    mut streaming: ResMut<LevelStreaming>,
+    levels: Res<Assets<Level>>,
+    triggers: Query<&TriggerVolume>,
+    members: Query<StreamedEntity>,
+) {
+    let Some(level) = levels.get(&streaming.level) else {
+        return;
+    };
+
+    // Rooms the player is standing in are always kept, so the floor under
+    // them is never unloaded even while the current room is changing.
+    let occupied = player_rooms(&triggers);
+    if occupied.is_empty() {
+        return;
+    }
+
+    let wanted = streaming.rooms_within(&occupied, STREAMING_RADIUS);
+    let unload: HashSet<RoomId> = streaming
+        .loaded
+        .iter()
+        .copied()
+        .filter(|room| !wanted.contains(room))
+        .collect();
+
+    if !unload.is_empty() {
+        for (entity, RoomMember(room), door, prop) in members.iter() {
+            if !unload.contains(room) {
+                continue;
+            }
+
+            let state = streaming.states.entry(*room).or_default();
+            if let Some(door) = door {
+                state.doors.insert(door.index, door.state);
+            }
+            if let Some((prop, transform)) = prop
+                && prop.dynamic
+            {
+                state.props.insert(prop.index, *transform);
+            }
+            commands.entity(entity).despawn();
+        }
+
+        for room in &unload {
+            debug!("Unloading room `{}`", level.rooms[room.0].name);
+            streaming.loaded.remove(room);
+        }
+    }
+
+    for room_id in wanted {
+        if streaming.is_loaded(room_id) {
+            continue;
+        }
+
+        let room = &level.rooms[room_id.0];
+        debug!("Loading room `{}`", room.name);
+        streaming.load_room(&mut commands, &mut meshes, &mut builder, room, room_id);
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::{
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::map::{DEFAULT_THEME, GROUND_TEXTURE_TILE, WALL_TEXTURE_TILE};
+
+// Texture paths are relative to the asset folder. The roughness map follows
+// the glTF layout, with roughness in the green channel and metalness in blue,
+// and both maps scale the plain values below.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SurfaceStyle {
+    pub texture: String,
+    pub normal_map: Option<String>,
+    pub roughness_map: Option<String>,
+    pub tile: f32,
+    pub tint: (f32, f32, f32),
+    pub roughness: f32,
+    pub metallic: f32,
+    pub reflectance: f32,
+}
+
+impl Default for SurfaceStyle {
+    fn default() -> Self {
+        Self {
+            texture: "map/bricks.png".to_string(),
+            normal_map: None,
+            roughness_map: None,
+            tile: WALL_TEXTURE_TILE,
+            tint: (1.0, 1.0, 1.0),
+            roughness: 0.5,
+            metallic: 0.0,
+            reflectance: 0.5,
+        }
+    }
+}
+
+impl SurfaceStyle {
+    // Uses the normal and roughness maps generated next to the texture.
+    fn mapped(name: &str, tile: f32, tint: (f32, f32, f32)) -> Self {
+        Self {
+            texture: format!("map/{name}.png"),
+            normal_map: Some(format!("map/{name}_normal.png")),
+            roughness_map: Some(format!("map/{name}_roughness.png")),
+            tile,
+            tint,
+            roughness: 1.0,
+            ..default()
+        }
+    }
+
+    pub fn material(&self, asset_server: &AssetServer) -> StandardMaterial {
+        let (red, green, blue) = self.tint;
+        StandardMaterial {
+            base_color: Color::srgb(red, green, blue),
+            base_color_texture: Some(load_repeating_texture(asset_server, &self.texture, true)),
+            normal_map_texture: self
+                .normal_map
+                .as_ref()
+                .map(|path| load_repeating_texture(asset_server, path, false)),
+            metallic_roughness_texture: self
+                .roughness_map
+                .as_ref()
+                .map(|path| load_repeating_texture(asset_server, path, false)),
+            perceptual_roughness: self.roughness,
+            metallic: self.metallic,
+            reflectance: self.reflectance,
+            uv_transform: Affine2::from_scale(Vec2::splat(1.0 / self.tile)),
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ThemeDef {
+    pub name: String,
+    #[serde(default)]
+    pub wall: SurfaceStyle,
+    #[serde(default)]
+    pub floor: SurfaceStyle,
+    #[serde(default)]
+    pub roof: SurfaceStyle,
+}
+
+#[derive(Clone, Debug)]
+pub struct ThemeMaterials {
+    pub wall: Handle<StandardMaterial>,
+    pub floor: Handle<StandardMaterial>,
+    pub roof: Handle<StandardMaterial>,
+}
+
+pub fn load_repeating_texture(asset_server: &AssetServer, path: &str, srgb: bool) -> Handle<Image> {
+    asset_server.load_with_settings(
+        path.to_string(),
+        move |settings: &mut ImageLoaderSettings| {
+            *settings = ImageLoaderSettings {
+                is_srgb: srgb,
+                sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
+                    address_mode_u: ImageAddressMode::Repeat,
+                    address_mode_v: ImageAddressMode::Repeat,
+                    ..default()
+                }),
+                ..default()
+            }
+        },
+    )
+}
+
+// The themes every level can use. Levels may add their own or replace these
+// by name.
+pub fn builtin_themes() -> Vec<ThemeDef> {
+    vec![
+        ThemeDef {
+            name: DEFAULT_THEME.to_string(),
+            wall: SurfaceStyle::default(),
+            floor: SurfaceStyle {
+                texture: "map/ground.png".to_string(),
+                tile: GROUND_TEXTURE_TILE,
+                roughness: 0.8,
+                reflectance: 0.4,
+                ..default()
+            },
+            roof: SurfaceStyle {
+                tile: GROUND_TEXTURE_TILE,
+                roughness: 1.0,
+                ..default()
+            },
+        },
+        ThemeDef {
+            name: "sewer".to_string(),
+            wall: SurfaceStyle::mapped("bricks", 6.0, (0.55, 0.65, 0.5)),
+            floor: SurfaceStyle {
+                roughness: 0.35,
+                reflectance: 0.7,
+                ..SurfaceStyle::mapped("ground", 8.0, (0.4, 0.5, 0.35))
+            },
+            roof: SurfaceStyle::mapped("bricks", 6.0, (0.35, 0.4, 0.32)),
+        },
+        ThemeDef {
+            name: "cathedral".to_string(),
+            wall: SurfaceStyle::mapped("bricks", 16.0, (0.92, 0.88, 0.8)),
+            floor: SurfaceStyle {
+                roughness: 0.3,
+                reflectance: 0.7,
+                ..SurfaceStyle::mapped("bricks", 20.0, (0.8, 0.78, 0.75))
+            },
+            roof: SurfaceStyle::mapped("bricks", 16.0, (0.6, 0.55, 0.5)),
+        },
+        ThemeDef {
+            name: "street".to_string(),
+            wall: SurfaceStyle::mapped("bricks", 8.0, (0.9, 0.6, 0.5)),
+            floor: SurfaceStyle::mapped("ground", 6.0, (0.55, 0.55, 0.55)),
+            roof: SurfaceStyle::mapped("ground", 10.0, (0.3, 0.3, 0.32)),
+        },
+        ThemeDef {
+            name: "vault".to_string(),
+            wall: SurfaceStyle {
+                metallic: 0.4,
+                ..SurfaceStyle::mapped("bricks", 5.0, (0.5, 0.5, 0.56))
+            },
+            floor: SurfaceStyle {
+                roughness: 0.6,
+                metallic: 0.5,
+                ..SurfaceStyle::mapped("bricks", 4.0, (0.42, 0.42, 0.48))
+            },
+            roof: SurfaceStyle {
+                metallic: 0.4,
+                ..SurfaceStyle::mapped("bricks", 5.0, (0.35, 0.35, 0.4))
+            },
+        },
+    ]
+}
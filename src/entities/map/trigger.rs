@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::map::{builder::MapBuilder, room_tracking::RoomId};
+
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum TriggerActor {
+    Player,
+    Enemy,
+    Projectile,
+    Prop,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum TriggerKind {
+    #[serde(skip)]
+    Room(RoomId),
+    Trap,
+    Prompt(String),
+    Checkpoint,
+    Cutscene(String),
+    #[serde(skip)]
+    Mechanism(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TriggerDef {
+    pub name: String,
+    pub kind: TriggerKind,
+    pub offset: (f32, f32, f32),
+    pub size: (f32, f32, f32),
+    #[serde(default = "default_filter")]
+    pub filter: Vec<TriggerActor>,
+}
+
+fn default_filter() -> Vec<TriggerActor> {
+    vec![TriggerActor::Player]
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct LastCheckpoint(pub Option<Vec3>);
+
+#[derive(Component, Debug)]
+pub struct TriggerVolume {
+    pub name: String,
+    pub kind: TriggerKind,
+    pub filter: Vec<TriggerActor>,
+    occupants: HashMap<Entity, TriggerActor>,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TriggerEntered {
+    pub trigger: Entity,
+    pub kind: TriggerKind,
+    pub actor: Entity,
+    pub actor_kind: TriggerActor,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TriggerStay {
+    pub trigger: Entity,
+    pub kind: TriggerKind,
+    pub actor: Entity,
+    pub actor_kind: TriggerActor,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct TriggerExited {
+    pub trigger: Entity,
+    pub kind: TriggerKind,
+    pub actor: Entity,
+    pub actor_kind: TriggerActor,
+}
+
+impl TriggerVolume {
+    pub fn new(name: impl Into<String>, kind: TriggerKind, filter: Vec<TriggerActor>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            filter,
+            occupants: HashMap::new(),
+        }
+    }
+
+    pub fn occupants(&self) -> impl Iterator<Item = (Entity, TriggerActor)> + '_ {
+        self.occupants
+            .iter()
+            .map(|(entity, actor)| (*entity, *actor))
+    }
+
+    pub fn contains_actor(&self, actor: TriggerActor) -> bool {
+        self.occupants.values().any(|kind| *kind == actor)
+    }
+}
+
+pub fn spawn_trigger<'a>(
+    commands: &'a mut Commands,
+    builder: &mut MapBuilder,
+    volume: TriggerVolume,
+    center: Vec3,
+    half_size: Vec3,
+) -> EntityCommands<'a> {
+    builder.spawn(
+        commands,
+        (
+            volume,
+            Transform::from_translation(center),
+            GlobalTransform::default(),
+            Collider::cuboid(half_size.x, half_size.y, half_size.z),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC
+                | ActiveCollisionTypes::STATIC_STATIC,
+        ),
+    )
+}
+
+pub fn spawn_level_triggers(
+    commands: &mut Commands,
+    builder: &mut MapBuilder,
+    defs: &[TriggerDef],
+) {
+    for def in defs {
+        let (x, y, z) = def.offset;
+        let (width, height, depth) = def.size;
+        spawn_trigger(
+            commands,
+            builder,
+            TriggerVolume::new(def.name.clone(), def.kind.clone(), def.filter.clone()),
+            Vec3::new(x, y, z),
+            Vec3::new(width, height, depth) / 2.0,
+        );
+    }
+}
+
+pub fn detect_triggers(
+    mut collisions: EventReader<CollisionEvent>,
+    mut triggers: Query<(Entity, &mut TriggerVolume)>,
+    actors: Query<&TriggerActor>,
+    mut entered: EventWriter<TriggerEntered>,
+    mut stay: EventWriter<TriggerStay>,
+    mut exited: EventWriter<TriggerExited>,
+) {
+    for collision in collisions.read() {
+        let (a, b, started) = match collision {
+            CollisionEvent::Started(a, b, _) => (*a, *b, true),
+            CollisionEvent::Stopped(a, b, _) => (*a, *b, false),
+        };
+
+        for (trigger, actor) in [(a, b), (b, a)] {
+            let Ok((_, mut volume)) = triggers.get_mut(trigger) else {
+                continue;
+            };
+
+            if started {
+                let Ok(actor_kind) = actors.get(actor) else {
+                    continue;
+                };
+                if !volume.filter.contains(actor_kind) {
+                    continue;
+                }
+                if volume.occupants.insert(actor, *actor_kind).is_none() {
+                    debug!("{:?} entered trigger `{}`", actor_kind, volume.name);
+                    entered.write(TriggerEntered {
+                        trigger,
+                        kind: volume.kind.clone(),
+                        actor,
+                        actor_kind: *actor_kind,
+                    });
+                }
+            } else if let Some(actor_kind) = volume.occupants.remove(&actor) {
+                debug!("{:?} left trigger `{}`", actor_kind, volume.name);
+                exited.write(TriggerExited {
+                    trigger,
+                    kind: volume.kind.clone(),
+                    actor,
+                    actor_kind,
+                });
+            }
+        }
+    }
+
+    for (trigger, volume) in triggers.iter() {
+        for (actor, actor_kind) in volume.occupants() {
+            stay.write(TriggerStay {
+                trigger,
+                kind: volume.kind.clone(),
+                actor,
+                actor_kind,
+            });
+        }
+    }
+}
+
+pub fn apply_trigger_actions(
+    mut entered: EventReader<TriggerEntered>,
+    mut stay: EventReader<TriggerStay>,
+    mut exited: EventReader<TriggerExited>,
+    transforms: Query<&GlobalTransform>,
+    mut checkpoint: ResMut<LastCheckpoint>,
+) {
+    for event in entered.read() {
+        match &event.kind {
+            TriggerKind::Checkpoint if event.actor_kind == TriggerActor::Player => {
+                if let Ok(transform) = transforms.get(event.trigger) {
+                    checkpoint.0 = Some(transform.translation());
+                    info!("Checkpoint reached at {}", transform.translation());
+                }
+            }
+            TriggerKind::Cutscene(name) => {
+                info!("Cutscene `{name}` started by {:?}", event.actor);
+            }
+            _ => {}
+        }
+    }
+
+    for event in stay.read() {
+        if event.kind == TriggerKind::Trap {
+            trace!(
+                "{:?} {:?} is inside trap {:?}",
+                event.actor_kind, event.actor, event.trigger
+            );
+        }
+    }
+
+    for event in exited.read() {
+        trace!(
+            "{:?} {:?} left {:?} ({:?})",
+            event.actor_kind, event.actor, event.trigger, event.kind
+        );
+    }
+}
@@ -1,22 +1,24 @@
-use std::collections::HashSet;
-
 use crate::{
-    constants::map::{BASE_ROOM_SIZE, GROUND_HEIGHT, ROOF_THICKNESS, WALL_THICKNESS},
-    entities::map::map::MapEntity,
-};
-use bevy::{
-    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
-    math::Affine2,
-    prelude::*,
+    constants::map::{GROUND_HEIGHT, ROOF_THICKNESS},
+    entities::map::{
+        builder::{MapBuilder, merge_meshes, tiled_box},
+        map::Room,
+        room_tracking::RoomId,
+        stairs::{Surface, spawn_stairs},
+        wall_mesh::{WallMeshBuilder, WallOpening},
+    },
 };
+use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum WallOrientation {
     AlongX,
     AlongZ,
 }
 
-#[derive(Debug, Hash, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Deserialize, Serialize)]
 pub enum WallType {
     NORTH,
     SOUTH,
@@ -33,431 +35,216 @@ impl WallType {
             WallType::EAST,
         ]
     }
-}
 
-pub fn make_north_wall(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    ground_size: f32,
-    commands: &mut Commands,
-    wall_material: &Handle<StandardMaterial>,
-    offset: Vec3,
-    wall_height: f32,
-    wall_thickness: f32,
-) {
-    let north_wall_mesh = meshes.add(Cuboid::new(ground_size * 2.0, wall_height, wall_thickness));
-    commands.spawn((
-        Mesh3d(north_wall_mesh),
-        MeshMaterial3d(wall_material.clone()),
-        Transform::from_xyz(
-            0.0 + offset.x,
-            wall_height / 2.0 + offset.y,
-            ground_size + offset.z,
-        ),
-        GlobalTransform::default(),
-        Collider::cuboid(ground_size, wall_height / 2.0, wall_thickness / 2.0),
-        MapEntity,
-    ));
-}
+    pub fn opposite(&self) -> WallType {
+        match self {
+            WallType::NORTH => WallType::SOUTH,
+            WallType::SOUTH => WallType::NORTH,
+            WallType::WEST => WallType::EAST,
+            WallType::EAST => WallType::WEST,
+        }
+    }
 
-pub fn make_south_wall(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    ground_size: f32,
-    commands: &mut Commands,
-    wall_material: &Handle<StandardMaterial>,
-    offset: Vec3,
-    wall_height: f32,
-    wall_thickness: f32,
-) {
-    let south_wall_mesh = meshes.add(Cuboid::new(ground_size * 2.0, wall_height, wall_thickness));
-    commands.spawn((
-        Mesh3d(south_wall_mesh),
-        MeshMaterial3d(wall_material.clone()),
-        Transform::from_xyz(
-            0.0 + offset.x,
-            wall_height / 2.0 + offset.y,
-            -ground_size + offset.z,
-        ),
-        GlobalTransform::default(),
-        Collider::cuboid(ground_size, wall_height / 2.0, wall_thickness / 2.0),
-        MapEntity,
-    ));
-}
+    pub fn orientation(&self) -> WallOrientation {
+        match self {
+            WallType::NORTH | WallType::SOUTH => WallOrientation::AlongX,
+            WallType::WEST | WallType::EAST => WallOrientation::AlongZ,
+        }
+    }
 
-pub fn make_west_wall(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    ground_size: f32,
-    commands: &mut Commands,
-    wall_material: &Handle<StandardMaterial>,
-    offset: Vec3,
-    wall_height: f32,
-    wall_thickness: f32,
-) {
-    let west_wall_mesh = meshes.add(Cuboid::new(wall_thickness, wall_height, ground_size * 2.0));
-    commands.spawn((
-        Mesh3d(west_wall_mesh),
-        MeshMaterial3d(wall_material.clone()),
-        Transform::from_xyz(
-            -ground_size + offset.x,
-            wall_height / 2.0 + offset.y,
-            0.0 + offset.z,
-        ),
-        GlobalTransform::default(),
-        Collider::cuboid(wall_thickness / 2.0, wall_height / 2.0, ground_size),
-        MapEntity,
-    ));
-}
+    pub fn along(&self) -> Vec3 {
+        match self.orientation() {
+            WallOrientation::AlongX => Vec3::X,
+            WallOrientation::AlongZ => Vec3::Z,
+        }
+    }
 
-pub fn make_east_wall(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    ground_size: f32,
-    commands: &mut Commands,
-    wall_material: &Handle<StandardMaterial>,
-    offset: Vec3,
-    wall_height: f32,
-    wall_thickness: f32,
-) {
-    let east_wall_mesh = meshes.add(Cuboid::new(wall_thickness, wall_height, ground_size * 2.0));
-    commands.spawn((
-        Mesh3d(east_wall_mesh),
-        MeshMaterial3d(wall_material.clone()),
-        Transform::from_xyz(
-            ground_size + offset.x,
-            wall_height / 2.0 + offset.y,
-            0.0 + offset.z,
-        ),
-        GlobalTransform::default(),
-        Collider::cuboid(wall_thickness / 2.0, wall_height / 2.0, ground_size),
-        MapEntity,
-    ));
+    pub fn normal(&self) -> Vec3 {
+        match self {
+            WallType::NORTH => Vec3::Z,
+            WallType::SOUTH => Vec3::NEG_Z,
+            WallType::WEST => Vec3::NEG_X,
+            WallType::EAST => Vec3::X,
+        }
+    }
 }
 
 pub fn make_room(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    wall_height: f32,
-    door_radius: f32,
-    offset: Vec3,
-    extension: f32,
-    empty_side: HashSet<WallType>,
-    door_side: HashSet<WallType>,
-    asset_server: &Res<AssetServer>,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
 ) {
-    let ground_size = BASE_ROOM_SIZE + extension;
-    let ground_height = GROUND_HEIGHT;
-    let ground_mesh = meshes.add(Cuboid::new(
-        ground_size * 2.0,
-        ground_height * 2.0,
-        ground_size * 2.0,
-    ));
-
-    let ground_color_texture = Some(asset_server.load_with_settings(
-        "map/ground.png",
-        |s: &mut _| {
-            *s = ImageLoaderSettings {
-                sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
-                    address_mode_u: ImageAddressMode::Repeat,
-                    address_mode_v: ImageAddressMode::Repeat,
-                    ..default()
-                }),
-                ..default()
-            }
-        },
-    ));
-
-    let ground_material = materials.add(StandardMaterial {
-        base_color_texture: ground_color_texture,
-        perceptual_roughness: 0.8,
-        reflectance: 0.4,
-        uv_transform: Affine2::from_scale(Vec2::new(ground_size / 6., ground_size / 6.)),
-        ..default()
-    });
-
-    commands.spawn((
-        Mesh3d(ground_mesh.clone()),
-        MeshMaterial3d(ground_material.clone()),
-        Transform::from_xyz(0.0 + offset.x, -ground_height + offset.y, 0.0 + offset.z),
-        GlobalTransform::default(),
-        Collider::cuboid(ground_size, ground_height, ground_size),
-        MapEntity,
-    ));
-
-    let wall_thickness = WALL_THICKNESS;
-    let bricks_texture = Some(
-        asset_server.load_with_settings("map/bricks.png", |s: &mut _| {
-            *s = ImageLoaderSettings {
-                sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
-                    address_mode_u: ImageAddressMode::Repeat,
-                    address_mode_v: ImageAddressMode::Repeat,
-                    ..default()
-                }),
-                ..default()
-            }
-        }),
-    );
-    let wall_material_ew = materials.add(StandardMaterial {
-        base_color_texture: bricks_texture.clone(),
-        uv_transform: Affine2::from_scale(Vec2::new(6., 1.))
-            * Affine2::from_angle(std::f32::consts::PI / 2.0),
-        ..default()
-    });
-
-    let wall_material_ns = materials.add(StandardMaterial {
-        base_color_texture: bricks_texture.clone(),
-        uv_transform: Affine2::from_scale(Vec2::new(6., 1.)),
-        ..default()
-    });
+    spawn_surface(commands, meshes, builder, room, room_id, Surface::Floor);
 
+    // Walls sharing a theme are merged into one mesh.
+    let mut walls: Vec<(Option<&str>, WallMeshBuilder)> = Vec::new();
     for wall_type in WallType::all() {
-        if !empty_side.contains(&wall_type) {
-            if door_side.contains(&wall_type) {
-                match wall_type {
-                    WallType::NORTH => spawn_wall_with_hole(
-                        commands,
-                        meshes,
-                        &wall_material_ns,
-                        bricks_texture.clone(),
-                        materials,
-                        wall_thickness,
-                        wall_height,
-                        door_radius,
-                        ground_size,
-                        Vec3::new(offset.x, offset.y, ground_size + offset.z),
-                        0.0,
-                        WallOrientation::AlongX,
-                    ),
-                    WallType::SOUTH => spawn_wall_with_hole(
-                        commands,
-                        meshes,
-                        &wall_material_ns,
-                        bricks_texture.clone(),
-                        materials,
-                        wall_thickness,
-                        wall_height,
-                        door_radius,
-                        ground_size,
-                        Vec3::new(offset.x, offset.y, -ground_size + offset.z),
-                        0.0,
-                        WallOrientation::AlongX,
-                    ),
-                    WallType::WEST => spawn_wall_with_hole(
-                        commands,
-                        meshes,
-                        &wall_material_ew,
-                        bricks_texture.clone(),
-                        materials,
-                        wall_thickness,
-                        wall_height,
-                        door_radius,
-                        ground_size,
-                        Vec3::new(-ground_size + offset.x, offset.y, offset.z),
-                        0.0,
-                        WallOrientation::AlongZ,
-                    ),
-                    WallType::EAST => spawn_wall_with_hole(
-                        commands,
-                        meshes,
-                        &wall_material_ew,
-                        bricks_texture.clone(),
-                        materials,
-                        wall_thickness,
-                        wall_height,
-                        door_radius,
-                        ground_size,
-                        Vec3::new(ground_size + offset.x, offset.y, offset.z),
-                        0.0,
-                        WallOrientation::AlongZ,
-                    ),
-                }
-            } else {
-                match wall_type {
-                    WallType::NORTH => make_north_wall(
-                        meshes,
-                        ground_size,
-                        commands,
-                        &wall_material_ns,
-                        offset,
-                        wall_height,
-                        wall_thickness,
-                    ),
-                    WallType::SOUTH => make_south_wall(
-                        meshes,
-                        ground_size,
-                        commands,
-                        &wall_material_ns,
-                        offset,
-                        wall_height,
-                        wall_thickness,
-                    ),
-                    WallType::WEST => make_west_wall(
-                        meshes,
-                        ground_size,
-                        commands,
-                        &wall_material_ew,
-                        offset,
-                        wall_height,
-                        wall_thickness,
-                    ),
-                    WallType::EAST => make_east_wall(
-                        meshes,
-                        ground_size,
-                        commands,
-                        &wall_material_ew,
-                        offset,
-                        wall_height,
-                        wall_thickness,
-                    ),
+        if !room.empty_side.contains(wall_type) {
+            let openings: Vec<WallOpening> = room
+                .doors_on(wall_type)
+                .map(|door| WallOpening {
+                    position: door.position,
+                    width: door.width,
+                    height: door.height,
+                    shape: door.shape.clone(),
+                })
+                .collect();
+
+            let theme = room.wall_theme(wall_type);
+            let index = match walls.iter().position(|(name, _)| *name == theme) {
+                Some(index) => index,
+                None => {
+                    walls.push((theme, WallMeshBuilder::default()));
+                    walls.len() - 1
                 }
-            }
+            };
+            walls[index].1.add_wall(room, wall_type, &openings);
         }
+    }
 
-        make_roof(
-            commands,
-            meshes,
-            materials,
-            bricks_texture.clone(),
-            wall_height,
-            offset,
-            extension,
-        );
+    for (theme, wall_mesh) in walls {
+        spawn_walls(commands, meshes, builder, room, room_id, theme, wall_mesh);
     }
+    spawn_stairs(commands, meshes, builder, room, room_id);
+    spawn_surface(commands, meshes, builder, room, room_id, Surface::Roof);
 }
 
-pub fn make_roof(
+fn spawn_walls(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    bricks_texture: Option<Handle<Image>>,
-    wall_height: f32,
-    offset: Vec3,
-    extension: f32,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+    theme: Option<&str>,
+    walls: WallMeshBuilder,
 ) {
-    let ground_size = BASE_ROOM_SIZE + extension;
-    let roof_thickness = ROOF_THICKNESS;
-
-    let roof_mesh = meshes.add(Cuboid::new(
-        ground_size * 2.0,
-        roof_thickness,
-        ground_size * 2.0,
-    ));
+    let Some((wall_mesh, collider)) = walls.build() else {
+        return;
+    };
+
+    let wall_mesh = builder.add_mesh(meshes, wall_mesh);
+    let wall_material = builder.theme(theme).wall;
+    builder.spawn_in_room(
+        commands,
+        room_id,
+        (
+            Mesh3d(wall_mesh),
+            MeshMaterial3d(wall_material),
+            Transform::from_translation(room.offset),
+            GlobalTransform::default(),
+            collider,
+        ),
+    );
+}
 
-    let roof_material = materials.add(StandardMaterial {
-        base_color_texture: bricks_texture,
-        perceptual_roughness: 1.0,
-        uv_transform: Affine2::from_scale(Vec2::new(ground_size / 6., ground_size / 6.)),
-        ..default()
-    });
+// Splits a floor or roof into rectangles around its openings.
+pub fn surface_pieces(half_extents: Vec2, openings: impl IntoIterator<Item = Rect>) -> Vec<Rect> {
+    let bounds = Rect::from_center_half_size(Vec2::ZERO, half_extents);
+    let holes: Vec<Rect> = openings
+        .into_iter()
+        .map(|opening| opening.intersect(bounds))
+        .filter(|hole| !hole.is_empty())
+        .collect();
+    if holes.is_empty() {
+        return vec![bounds];
+    }
 
-    commands.spawn((
-        Mesh3d(roof_mesh),
-        MeshMaterial3d(roof_material.clone()),
-        Transform::from_xyz(
-            0.0 + offset.x,
-            wall_height + roof_thickness / 2.0 + offset.y,
-            0.0 + offset.z,
-        ),
-        GlobalTransform::default(),
-        Collider::cuboid(ground_size, roof_thickness / 2.0, ground_size),
-        MapEntity,
-    ));
+    let cuts = |axis: fn(Vec2) -> f32| {
+        let mut cuts: Vec<f32> = [bounds.min, bounds.max]
+            .into_iter()
+            .chain(holes.iter().flat_map(|hole| [hole.min, hole.max]))
+            .map(axis)
+            .collect();
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup();
+        cuts
+    };
+    let (xs, zs) = (cuts(|point| point.x), cuts(|point| point.y));
+
+    let mut pieces = Vec::new();
+    for z in zs.windows(2) {
+        let mut strip: Option<Rect> = None;
+        for x in xs.windows(2) {
+            let cell = Rect::new(x[0], z[0], x[1], z[1]);
+            if holes.iter().any(|hole| hole.contains(cell.center())) {
+                pieces.extend(strip.take());
+            } else if let Some(strip) = &mut strip {
+                strip.max.x = cell.max.x;
+            } else {
+                strip = Some(cell);
+            }
+        }
+        pieces.extend(strip);
+    }
+    pieces
 }
 
-pub fn spawn_wall_with_hole(
+fn spawn_surface(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
-    wall_material: &Handle<StandardMaterial>,
-    wall_color_texture: Option<Handle<Image>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-
-    wall_thickness: f32,
-    wall_height: f32,
-    door_radius: f32,
-    ground_size: f32,
-    offset: Vec3,
-    extension: f32,
-    orientation: WallOrientation,
+    builder: &mut MapBuilder,
+    room: &Room,
+    room_id: RoomId,
+    surface: Surface,
 ) {
-    let extended_size = ground_size + extension;
-
-    let hole_h = door_radius * 2.0;
-    let top_h = wall_height - hole_h;
-    let top_y = hole_h + top_h / 2.0;
-
-    match orientation {
-        WallOrientation::AlongZ => {
-            let wall_material_top = materials.add(StandardMaterial {
-                base_color_texture: wall_color_texture.clone(),
-                uv_transform: Affine2::from_scale(Vec2::new(6., 0.5))
-                    * Affine2::from_angle(std::f32::consts::PI / 2.0),
-                ..default()
-            });
-            let top_mesh = meshes.add(Cuboid::new(wall_thickness, top_h, extended_size * 2.0));
-            commands.spawn((
-                Mesh3d(top_mesh),
-                MeshMaterial3d(wall_material_top.clone()),
-                Transform::from_xyz(offset.x, top_y + offset.y, offset.z).with_scale(Vec3::ONE),
-                GlobalTransform::default(),
-                Collider::cuboid(wall_thickness / 2.0, top_h / 2.0, extended_size),
-                MapEntity,
-            ));
-
-            let side_w = extended_size - door_radius;
-            let side_z = (extended_size + door_radius) / 2.0;
-            //TODO: Have to fix this, subtracting by 4 is only temperory measure. It just pushed the wall bellow surface.
-            let side_h = wall_height - top_h - 4.;
-
-            let mut spawn_side = |z_center: f32| {
-                let mesh = meshes.add(Cuboid::new(wall_thickness, wall_height, side_w));
-                commands.spawn((
-                    Mesh3d(mesh),
-                    MeshMaterial3d(wall_material.clone()),
-                    Transform::from_xyz(offset.x, side_h / 2.0 + offset.y, z_center + offset.z),
-                    GlobalTransform::default(),
-                    Collider::cuboid(wall_thickness / 2.0, side_h / 2.0, side_w / 2.0),
-                    MapEntity,
-                ));
-            };
-
-            spawn_side(-side_z);
-            spawn_side(side_z);
-        }
-
-        WallOrientation::AlongX => {
-            let wall_material_top = materials.add(StandardMaterial {
-                base_color_texture: wall_color_texture.clone(),
-                uv_transform: Affine2::from_scale(Vec2::new(6., 0.5)),
-                ..default()
-            });
-
-            let top_mesh = meshes.add(Cuboid::new(extended_size * 2.0, top_h, wall_thickness));
-            commands.spawn((
-                Mesh3d(top_mesh),
-                MeshMaterial3d(wall_material_top),
-                Transform::from_xyz(offset.x, top_y + offset.y, offset.z),
-                GlobalTransform::default(),
-                Collider::cuboid(extended_size, top_h / 2.0, wall_thickness / 2.0),
-                MapEntity,
-            ));
-
-            let side_w = extended_size - door_radius;
-            let side_x = (extended_size + door_radius) / 2.0;
-            //TODO: Have to fix this, subtracting by 4 is only temperory measure. It just pushed the wall bellow surface.
-            let side_h = wall_height - top_h - 4.;
-
-            let mut spawn_side = |x_center: f32| {
-                let mesh = meshes.add(Cuboid::new(side_w, wall_height, wall_thickness));
-                commands.spawn((
-                    Mesh3d(mesh),
-                    MeshMaterial3d(wall_material.clone()),
-                    Transform::from_xyz(x_center + offset.x, side_h / 2.0 + offset.y, offset.z),
-                    GlobalTransform::default(),
-                    Collider::cuboid(side_w / 2.0, side_h / 2.0, wall_thickness / 2.0),
-                    MapEntity,
-                ));
-            };
-
-            spawn_side(-side_x);
-            spawn_side(side_x);
-        }
-    }
+    let half_extents = room.half_extents();
+    let theme = builder.theme(room.theme.as_deref());
+    let (kind, thickness, center_y, material) = match surface {
+        Surface::Floor => ("ground", GROUND_HEIGHT * 2.0, -GROUND_HEIGHT, theme.floor),
+        Surface::Roof => (
+            "roof",
+            ROOF_THICKNESS,
+            room.wall_height + ROOF_THICKNESS / 2.0,
+            theme.roof,
+        ),
+    };
+
+    let pieces = surface_pieces(half_extents, room.openings_in(surface));
+    let (mesh, collider) = if let [whole] = pieces[..] {
+        let size = Vec3::new(whole.width(), thickness, whole.height());
+        let mesh = builder.cached_mesh(meshes, kind, size, || tiled_box(Vec3::ZERO, size));
+        let half_size = size / 2.0;
+        let collider = Collider::cuboid(half_size.x, half_size.y, half_size.z);
+        (mesh, collider)
+    } else {
+        let boxes: Vec<(Vec3, Vec3)> = pieces
+            .iter()
+            .map(|piece| {
+                let center = Vec3::new(piece.center().x, 0.0, piece.center().y);
+                (center, Vec3::new(piece.width(), thickness, piece.height()))
+            })
+            .collect();
+        let Some(mesh) = merge_meshes(boxes.iter().map(|&(center, size)| tiled_box(center, size)))
+        else {
+            return;
+        };
+        let collider = Collider::compound(
+            boxes
+                .iter()
+                .map(|&(center, size)| {
+                    let half_size = size / 2.0;
+                    (
+                        center,
+                        Quat::IDENTITY,
+                        Collider::cuboid(half_size.x, half_size.y, half_size.z),
+                    )
+                })
+                .collect(),
+        );
+        (builder.add_mesh(meshes, mesh), collider)
+    };
+
+    builder.spawn_in_room(
+        commands,
+        room_id,
+        (
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::from_translation(room.offset + Vec3::Y * center_y),
+            GlobalTransform::default(),
+            collider,
+        ),
+    );
 }
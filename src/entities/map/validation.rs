@@ -0,0 +1,573 @@
+use std::{collections::VecDeque, fmt};
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::map::{ENTRY_ROOM_NAME, WALL_THICKNESS},
+    entities::map::{
+        map::{DoorOpening, Room},
+        mechanism::{MechanismDef, MechanismKind},
+        stairs::{StairDef, Surface},
+        utility::WallType,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutIssue {
+    Overlap {
+        room: String,
+        other: String,
+    },
+    DoorIntoWall {
+        room: String,
+        side: WallType,
+        neighbour: String,
+    },
+    DoorIntoVoid {
+        room: String,
+        side: WallType,
+    },
+    OpenSideIntoVoid {
+        room: String,
+        side: WallType,
+    },
+    DoorOutsideWall {
+        room: String,
+        side: WallType,
+    },
+    DoorOnOpenSide {
+        room: String,
+        side: WallType,
+    },
+    DoorsOverlap {
+        room: String,
+        side: WallType,
+    },
+    OpeningIntoVoid {
+        room: String,
+    },
+    StairsOutsideRoom {
+        room: String,
+    },
+    Unreachable {
+        room: String,
+    },
+    MissingEntry,
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutIssue::Overlap { room, other } => {
+                write!(f, "rooms `{room}` and `{other}` overlap")
+            }
+            LayoutIssue::DoorIntoWall {
+                room,
+                side,
+                neighbour,
+            } => write!(
+                f,
+                "{side:?} door of `{room}` opens into a solid wall of `{neighbour}`"
+            ),
+            LayoutIssue::DoorIntoVoid { room, side } => {
+                write!(f, "{side:?} door of `{room}` opens into empty space")
+            }
+            LayoutIssue::OpenSideIntoVoid { room, side } => {
+                write!(f, "{side:?} side of `{room}` is open to empty space")
+            }
+            LayoutIssue::DoorOutsideWall { room, side } => {
+                write!(
+                    f,
+                    "a {side:?} door of `{room}` does not fit inside its wall"
+                )
+            }
+            LayoutIssue::DoorOnOpenSide { room, side } => {
+                write!(f, "`{room}` has a door on its open {side:?} side")
+            }
+            LayoutIssue::DoorsOverlap { room, side } => {
+                write!(f, "{side:?} doors of `{room}` overlap each other")
+            }
+            LayoutIssue::OpeningIntoVoid { room } => {
+                write!(
+                    f,
+                    "a roof opening of `{room}` has no floor opening above it"
+                )
+            }
+            LayoutIssue::StairsOutsideRoom { room } => {
+                write!(f, "a staircase of `{room}` does not fit inside the room")
+            }
+            LayoutIssue::Unreachable { room } => {
+                write!(f, "`{room}` cannot be reached from `{ENTRY_ROOM_NAME}`")
+            }
+            LayoutIssue::MissingEntry => write!(f, "no room is named `{ENTRY_ROOM_NAME}`"),
+        }
+    }
+}
+
+fn contains_point(room: &Room, point: Vec3) -> bool {
+    let (min, max) = room.get_bounds();
+    point.cmpge(min).all() && point.cmple(max).all()
+}
+
+fn overlaps(room: &Room, other: &Room) -> bool {
+    let (min, max) = room.get_bounds();
+    let (other_min, other_max) = other.get_bounds();
+    min.cmplt(other_max).all() && other_min.cmplt(max).all()
+}
+
+fn neighbour_through(rooms: &[Room], index: usize, side: &WallType, point: Vec3) -> Option<usize> {
+    let probe = point + side.normal() * WALL_THICKNESS;
+    (0..rooms.len()).find(|&other| other != index && contains_point(&rooms[other], probe))
+}
+
+// An open side must have a room behind its whole length, not just its middle.
+fn open_side_covered(rooms: &[Room], index: usize, side: &WallType) -> bool {
+    let room = &rooms[index];
+    let reach = room.wall_half_length(side) - WALL_THICKNESS;
+    [-reach, reach].into_iter().all(|position| {
+        let point = room.wall_center(side) + side.along() * position;
+        neighbour_through(rooms, index, side, point).is_some()
+    })
+}
+
+fn shares_wall(room: &Room, side: &WallType, neighbour: &Room) -> bool {
+    let facing = side.opposite();
+    let gap = room.wall_center(side) - neighbour.wall_center(&facing);
+    gap.dot(side.normal()).abs() < f32::EPSILON.sqrt()
+}
+
+fn connects(room: &Room, side: &WallType, point: Vec3, is_door: bool, neighbour: &Room) -> bool {
+    let facing = side.opposite();
+    if !shares_wall(room, side, neighbour) || neighbour.empty_side.contains(&facing) {
+        return true;
+    }
+
+    neighbour.doors_on(&facing).any(|door| {
+        let offset = (point - neighbour.door_center(door))
+            .dot(side.along())
+            .abs();
+        if is_door {
+            offset <= door.width / 2.0
+        } else {
+            // An open side lines up with any door that lies along it.
+            offset + door.width / 2.0 <= room.wall_half_length(side)
+        }
+    })
+}
+
+fn door_fits(room: &Room, door: &DoorOpening) -> bool {
+    door.width > 0.0
+        && door.height > 0.0
+        && door.height <= room.wall_height
+        && door.position.abs() + door.width / 2.0 <= room.wall_half_length(&door.side)
+}
+
+fn doors_overlap(room: &Room, side: &WallType) -> bool {
+    let mut spans: Vec<(f32, f32)> = room
+        .doors_on(side)
+        .map(|door| {
+            (
+                door.position - door.width / 2.0,
+                door.position + door.width / 2.0,
+            )
+        })
+        .collect();
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+    spans.windows(2).any(|pair| pair[1].0 < pair[0].1)
+}
+
+fn world_rect(room: &Room, local: Rect) -> Rect {
+    Rect::from_center_size(local.center() + room.offset.xz(), local.size())
+}
+
+// The room stacked on top of `room` whose floor opening lines up with `opening`.
+fn room_above(rooms: &[Room], room: &Room, opening: Rect) -> Option<usize> {
+    let opening = world_rect(room, opening);
+    let ceiling = room.offset.y + room.storey_height();
+    rooms.iter().position(|other| {
+        (other.offset.y - ceiling).abs() < f32::EPSILON.sqrt()
+            && other
+                .openings_in(Surface::Floor)
+                .any(|floor| !world_rect(other, floor).intersect(opening).is_empty())
+    })
+}
+
+fn stairs_fit(room: &Room, stairs: &StairDef) -> bool {
+    let interior = Rect::from_center_half_size(
+        Vec2::ZERO,
+        room.half_extents() - Vec2::splat(WALL_THICKNESS / 2.0),
+    );
+    stairs.rise > 0.0
+        && stairs.width > 0.0
+        && interior.contains(stairs.footprint().min)
+        && interior.contains(stairs.footprint().max)
+}
+
+struct Analysis {
+    issues: Vec<LayoutIssue>,
+    links: Vec<Vec<usize>>,
+    // Links that pass through a locked door, as (room, door index, neighbour).
+    locked: Vec<(usize, usize, usize)>,
+}
+
+fn analyse_layout(rooms: &[Room]) -> Analysis {
+    let mut issues = Vec::new();
+    let mut links: Vec<Vec<usize>> = vec![Vec::new(); rooms.len()];
+    let mut locked = Vec::new();
+
+    for (index, room) in rooms.iter().enumerate() {
+        for other in rooms.iter().skip(index + 1) {
+            if overlaps(room, other) {
+                issues.push(LayoutIssue::Overlap {
+                    room: room.name.clone(),
+                    other: other.name.clone(),
+                });
+            }
+        }
+
+        for opening in room.openings_in(Surface::Roof) {
+            match room_above(rooms, room, opening) {
+                Some(above) => {
+                    links[index].push(above);
+                    links[above].push(index);
+                }
+                None => issues.push(LayoutIssue::OpeningIntoVoid {
+                    room: room.name.clone(),
+                }),
+            }
+        }
+
+        if !room.stairs.iter().all(|stairs| stairs_fit(room, stairs)) {
+            issues.push(LayoutIssue::StairsOutsideRoom {
+                room: room.name.clone(),
+            });
+        }
+
+        for side in WallType::all() {
+            let is_empty = room.empty_side.contains(side);
+            let mut openings = Vec::new();
+            if is_empty {
+                openings.push((room.wall_center(side), None));
+            }
+
+            for (door_index, door) in room.doors.iter().enumerate() {
+                if door.side != *side {
+                    continue;
+                }
+                if !door_fits(room, door) {
+                    issues.push(LayoutIssue::DoorOutsideWall {
+                        room: room.name.clone(),
+                        side: *side,
+                    });
+                }
+                if is_empty {
+                    issues.push(LayoutIssue::DoorOnOpenSide {
+                        room: room.name.clone(),
+                        side: *side,
+                    });
+                } else {
+                    openings.push((room.door_center(door), Some(door_index)));
+                }
+            }
+
+            if doors_overlap(room, side) {
+                issues.push(LayoutIssue::DoorsOverlap {
+                    room: room.name.clone(),
+                    side: *side,
+                });
+            }
+
+            for (point, door_index) in openings {
+                let is_door = door_index.is_some();
+                let Some(neighbour_index) = neighbour_through(rooms, index, side, point) else {
+                    issues.push(if is_door {
+                        LayoutIssue::DoorIntoVoid {
+                            room: room.name.clone(),
+                            side: *side,
+                        }
+                    } else {
+                        LayoutIssue::OpenSideIntoVoid {
+                            room: room.name.clone(),
+                            side: *side,
+                        }
+                    });
+                    continue;
+                };
+
+                if !is_door && !open_side_covered(rooms, index, side) {
+                    issues.push(LayoutIssue::OpenSideIntoVoid {
+                        room: room.name.clone(),
+                        side: *side,
+                    });
+                }
+
+                let neighbour = &rooms[neighbour_index];
+                if connects(room, side, point, is_door, neighbour) {
+                    links[index].push(neighbour_index);
+                    links[neighbour_index].push(index);
+                    if let Some(door_index) = door_index
+                        && room.doors[door_index].locked
+                    {
+                        locked.push((index, door_index, neighbour_index));
+                    }
+                } else if is_door {
+                    issues.push(LayoutIssue::DoorIntoWall {
+                        room: room.name.clone(),
+                        side: *side,
+                        neighbour: neighbour.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    for neighbours in &mut links {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    Analysis {
+        issues,
+        links,
+        locked,
+    }
+}
+
+pub fn room_graph(rooms: &[Room]) -> Vec<Vec<usize>> {
+    analyse_layout(rooms).links
+}
+
+fn unlocked_by(mechanisms: &[MechanismDef], room: &Room, index: usize) -> bool {
+    let opening = &room.doors[index];
+    mechanisms.iter().any(|mechanism| {
+        matches!(&mechanism.kind, MechanismKind::Door { room: name, side, door, .. }
+            if *name == room.name
+                && *side == opening.side
+                && door.is_none_or(|door| door == index))
+    })
+}
+
+// A locked door only counts towards reachability when some mechanism can open it.
+pub fn validate_layout(rooms: &[Room], mechanisms: &[MechanismDef]) -> Vec<LayoutIssue> {
+    let Analysis {
+        mut issues,
+        mut links,
+        locked,
+    } = analyse_layout(rooms);
+    for (index, door_index, neighbour) in locked {
+        if !unlocked_by(mechanisms, &rooms[index], door_index) {
+            links[index].retain(|&next| next != neighbour);
+            links[neighbour].retain(|&next| next != index);
+        }
+    }
+
+    let Some(entry) = rooms.iter().position(|room| room.name == ENTRY_ROOM_NAME) else {
+        issues.push(LayoutIssue::MissingEntry);
+        return issues;
+    };
+
+    let mut reached = vec![false; rooms.len()];
+    let mut queue = VecDeque::from([entry]);
+    reached[entry] = true;
+    while let Some(index) = queue.pop_front() {
+        for &next in &links[index] {
+            if !reached[next] {
+                reached[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    for (room, reached) in rooms.iter().zip(reached) {
+        if !reached {
+            issues.push(LayoutIssue::Unreachable {
+                room: room.name.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+    use crate::{constants::map::BASE_ROOM_SIZE, entities::map::wall_mesh::DoorShape};
+
+    fn room(name: &str, x: f32, z: f32) -> Room {
+        Room {
+            name: name.to_string(),
+            wall_height: 10.0,
+            extension_x: 0.0,
+            extension_z: 0.0,
+            empty_side: HashSet::new(),
+            doors: Vec::new(),
+            offset: Vec3::new(x, 0.0, z),
+            lighting: default(),
+            props: Vec::new(),
+            scatter: Vec::new(),
+            stairs: Vec::new(),
+            openings: Vec::new(),
+            theme: None,
+            wall_themes: HashMap::new(),
+        }
+    }
+
+    fn door(side: WallType, position: f32) -> DoorOpening {
+        DoorOpening {
+            side,
+            position,
+            width: 6.0,
+            height: 6.0,
+            locked: false,
+            secret: false,
+            shape: DoorShape::Rectangle,
+        }
+    }
+
+    // Two rooms side by side along x whose east and west walls meet.
+    fn pair() -> (Room, Room) {
+        let size = BASE_ROOM_SIZE * 2.0;
+        (room(ENTRY_ROOM_NAME, 0.0, 0.0), room("East", size, 0.0))
+    }
+
+    #[test]
+    fn door_into_open_side_links_rooms() {
+        let (mut entry, mut east) = pair();
+        entry.doors.push(door(WallType::EAST, 0.0));
+        east.empty_side.insert(WallType::WEST);
+
+        let Analysis { issues, links, .. } = analyse_layout(&[entry, east]);
+        assert_eq!(issues, Vec::new());
+        assert_eq!(links, vec![vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn overlapping_rooms_are_reported() {
+        let rooms = [room(ENTRY_ROOM_NAME, 0.0, 0.0), room("Inside", 10.0, 0.0)];
+
+        let Analysis { issues, .. } = analyse_layout(&rooms);
+        assert!(issues.contains(&LayoutIssue::Overlap {
+            room: ENTRY_ROOM_NAME.to_string(),
+            other: "Inside".to_string(),
+        }));
+    }
+
+    #[test]
+    fn misaligned_doors_open_into_a_wall() {
+        let (mut entry, mut east) = pair();
+        entry.doors.push(door(WallType::EAST, 0.0));
+        east.doors.push(door(WallType::WEST, 10.0));
+
+        let Analysis { issues, links, .. } = analyse_layout(&[entry, east]);
+        assert!(issues.contains(&LayoutIssue::DoorIntoWall {
+            room: ENTRY_ROOM_NAME.to_string(),
+            side: WallType::EAST,
+            neighbour: "East".to_string(),
+        }));
+        assert_eq!(links, vec![Vec::<usize>::new(), Vec::new()]);
+    }
+
+    #[test]
+    fn open_side_misses_a_door_beyond_its_end() {
+        let (mut entry, mut east) = pair();
+        entry.empty_side.insert(WallType::EAST);
+        east.extension_z = BASE_ROOM_SIZE;
+        east.doors.push(door(WallType::WEST, BASE_ROOM_SIZE * 1.5));
+
+        let Analysis { links, .. } = analyse_layout(&[entry, east]);
+        assert_eq!(links, vec![Vec::<usize>::new(), Vec::new()]);
+    }
+
+    #[test]
+    fn door_without_neighbour_opens_into_void() {
+        let mut entry = room(ENTRY_ROOM_NAME, 0.0, 0.0);
+        entry.doors.push(door(WallType::NORTH, 0.0));
+
+        let Analysis { issues, .. } = analyse_layout(&[entry]);
+        assert_eq!(
+            issues,
+            vec![LayoutIssue::DoorIntoVoid {
+                room: ENTRY_ROOM_NAME.to_string(),
+                side: WallType::NORTH,
+            }]
+        );
+    }
+
+    #[test]
+    fn rooms_without_a_path_are_unreachable() {
+        let (entry, east) = pair();
+        let far = room("Far", 0.0, 500.0);
+
+        let Analysis { links, .. } = analyse_layout(&[entry.clone(), east.clone(), far.clone()]);
+        assert!(links.iter().all(Vec::is_empty));
+        let issues = validate_layout(&[entry, east, far], &[]);
+        assert_eq!(
+            issues,
+            vec![
+                LayoutIssue::Unreachable {
+                    room: "East".to_string()
+                },
+                LayoutIssue::Unreachable {
+                    room: "Far".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn locked_door_needs_a_mechanism_to_open_it() {
+        let (mut entry, mut east) = pair();
+        entry.doors.push(DoorOpening {
+            locked: true,
+            ..door(WallType::EAST, 0.0)
+        });
+        east.empty_side.insert(WallType::WEST);
+        let rooms = [entry, east];
+
+        assert_eq!(
+            validate_layout(&rooms, &[]),
+            vec![LayoutIssue::Unreachable {
+                room: "East".to_string()
+            }]
+        );
+
+        let lever = MechanismDef {
+            name: "Lever".to_string(),
+            kind: MechanismKind::Lever {
+                offset: (0.0, 0.0, 0.0),
+            },
+        };
+        let door = MechanismDef {
+            name: "Door".to_string(),
+            kind: MechanismKind::Door {
+                input: "Lever".to_string(),
+                room: ENTRY_ROOM_NAME.to_string(),
+                side: WallType::EAST,
+                door: None,
+            },
+        };
+        assert_eq!(
+            validate_layout(&rooms, &[lever.clone(), door.clone()]),
+            Vec::new()
+        );
+
+        let other_door = MechanismDef {
+            kind: MechanismKind::Door {
+                input: "Lever".to_string(),
+                room: ENTRY_ROOM_NAME.to_string(),
+                side: WallType::EAST,
+                door: Some(1),
+            },
+            ..door
+        };
+        assert_eq!(
+            validate_layout(&rooms, &[lever, other_door]),
+            vec![LayoutIssue::Unreachable {
+                room: "East".to_string()
+            }]
+        );
+    }
+}
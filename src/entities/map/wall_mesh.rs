@@ -0,0 +1,340 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::map::{ARCH_SEGMENTS, WALL_GEOMETRY_EPSILON, WALL_THICKNESS},
+    entities::map::{builder::tiled_uv, map::Room, utility::WallType},
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum DoorShape {
+    Rectangle,
+    // Round-topped, which is what a door radius describes.
+    #[default]
+    Arch,
+    // Points along the wall from the door centre and up from the floor.
+    Outline(Vec<(f32, f32)>),
+}
+
+pub struct WallOpening {
+    pub position: f32,
+    pub width: f32,
+    pub height: f32,
+    pub shape: DoorShape,
+}
+
+impl DoorShape {
+    pub fn outline(&self, width: f32, height: f32) -> Vec<Vec2> {
+        let half_width = width / 2.0;
+        let mut points = match self {
+            DoorShape::Rectangle => vec![
+                Vec2::new(-half_width, 0.0),
+                Vec2::new(half_width, 0.0),
+                Vec2::new(half_width, height),
+                Vec2::new(-half_width, height),
+            ],
+            DoorShape::Arch => {
+                let spring = (height - half_width).max(0.0);
+                let rise = height - spring;
+                let mut points = vec![Vec2::new(-half_width, 0.0), Vec2::new(half_width, 0.0)];
+                points.extend((0..=ARCH_SEGMENTS).map(|segment| {
+                    let angle = std::f32::consts::PI * segment as f32 / ARCH_SEGMENTS as f32;
+                    Vec2::new(half_width * angle.cos(), spring + rise * angle.sin())
+                }));
+                points
+            }
+            DoorShape::Outline(points) => points.iter().copied().map(Vec2::from).collect(),
+        };
+
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum();
+        if area < 0.0 {
+            points.reverse();
+        }
+        points
+    }
+}
+
+// The vertical extent of an opening over one strip of wall, as the bottom and
+// top edges at both ends of the strip. Outlines are treated as vertically
+// convex, so any gap inside a column is filled in.
+#[derive(Clone, Copy)]
+struct Span {
+    bottom: (f32, f32),
+    top: (f32, f32),
+}
+
+impl Span {
+    fn bottom_mid(&self) -> f32 {
+        (self.bottom.0 + self.bottom.1) / 2.0
+    }
+
+    fn top_mid(&self) -> f32 {
+        (self.top.0 + self.top.1) / 2.0
+    }
+}
+
+fn span(outline: &[Vec2], start: f32, end: f32, height: f32) -> Option<Span> {
+    let middle = (start + end) / 2.0;
+    let mut edges: Vec<(f32, f32, f32)> = outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .filter(|(a, b)| (a.x - middle) * (b.x - middle) < 0.0)
+        .map(|(a, b)| {
+            let at = |x: f32| a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x);
+            (at(middle), at(start), at(end))
+        })
+        .collect();
+    if edges.len() < 2 {
+        return None;
+    }
+
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (_, bottom_start, bottom_end) = edges[0];
+    let (_, top_start, top_end) = edges[edges.len() - 1];
+    let clamp = |y: f32| y.clamp(0.0, height);
+    Some(Span {
+        bottom: (clamp(bottom_start), clamp(bottom_end)),
+        top: (clamp(top_start), clamp(top_end)),
+    })
+}
+
+#[derive(Default)]
+pub struct WallMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+    colliders: Vec<(Vect, Rot, Collider)>,
+}
+
+impl WallMeshBuilder {
+    fn quad(&mut self, mut corners: [Vec3; 4], normal: Vec3) {
+        let facing: f32 = (0..4)
+            .map(|i| corners[i].cross(corners[(i + 1) % 4]).dot(normal))
+            .sum();
+        if facing.abs() <= f32::EPSILON {
+            return;
+        }
+        if facing < 0.0 {
+            corners.reverse();
+        }
+
+        let base = self.positions.len() as u32;
+        for corner in corners {
+            self.positions.push(corner.to_array());
+            self.normals.push(normal.to_array());
+            self.uvs.push(tiled_uv(corner, normal));
+        }
+        self.indices
+            .extend([0, 1, 2, 0, 2, 3].into_iter().map(|index| base + index));
+    }
+
+    // Adds one side of a room, cut into vertical strips at every corner of
+    // its openings so each solid piece is a trapezoid with its own convex
+    // collider.
+    pub fn add_wall(&mut self, room: &Room, wall_type: &WallType, openings: &[WallOpening]) {
+        let half_length = room.wall_half_length(wall_type);
+        let height = room.wall_height;
+        let half_thickness = WALL_THICKNESS / 2.0;
+        let center = room.wall_center(wall_type) - room.offset;
+        let along = wall_type.along();
+        let depth = wall_type.normal().abs();
+        let point = |x: f32, y: f32, z: f32| center + along * x + Vec3::Y * y + depth * z;
+
+        let outlines: Vec<Vec<Vec2>> = openings
+            .iter()
+            .map(|opening| {
+                opening
+                    .shape
+                    .outline(opening.width, opening.height)
+                    .into_iter()
+                    .map(|corner| corner + Vec2::X * opening.position)
+                    .collect()
+            })
+            .collect();
+
+        let mut cuts: Vec<f32> = outlines
+            .iter()
+            .flatten()
+            .map(|corner| corner.x.clamp(-half_length, half_length))
+            .chain([-half_length, half_length])
+            .collect();
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup_by(|a, b| (*a - *b).abs() < WALL_GEOMETRY_EPSILON);
+
+        for strip in cuts.windows(2) {
+            let (start, end) = (strip[0], strip[1]);
+            let mut spans: Vec<Span> = outlines
+                .iter()
+                .filter_map(|outline| span(outline, start, end, height))
+                .collect();
+            spans.sort_by(|a, b| a.bottom_mid().total_cmp(&b.bottom_mid()));
+
+            let mut below = (0.0, 0.0);
+            let mut solids = Vec::new();
+            for span in spans {
+                if span.bottom_mid() > (below.0 + below.1) / 2.0 + WALL_GEOMETRY_EPSILON {
+                    solids.push((below, span.bottom));
+                }
+                if span.top_mid() > (below.0 + below.1) / 2.0 {
+                    below = span.top;
+                }
+            }
+            if height > (below.0 + below.1) / 2.0 + WALL_GEOMETRY_EPSILON {
+                solids.push((below, (height, height)));
+            }
+
+            for (bottom, top) in solids {
+                let top = (top.0.max(bottom.0), top.1.max(bottom.1));
+                let face = |z: f32| {
+                    [
+                        point(start, bottom.0, z),
+                        point(end, bottom.1, z),
+                        point(end, top.1, z),
+                        point(start, top.0, z),
+                    ]
+                };
+                let (front, back) = (face(half_thickness), face(-half_thickness));
+                self.quad(front, depth);
+                self.quad(back, -depth);
+
+                if start <= -half_length + WALL_GEOMETRY_EPSILON {
+                    self.quad([back[0], front[0], front[3], back[3]], -along);
+                }
+                if end >= half_length - WALL_GEOMETRY_EPSILON {
+                    self.quad([back[1], front[1], front[2], back[2]], along);
+                }
+                if top.0.min(top.1) >= height - WALL_GEOMETRY_EPSILON {
+                    self.quad([back[3], back[2], front[2], front[3]], Vec3::Y);
+                }
+
+                let corners: Vec<Vec3> = front.into_iter().chain(back).collect();
+                if let Some(collider) = Collider::convex_hull(&corners) {
+                    self.colliders.push((Vec3::ZERO, Quat::IDENTITY, collider));
+                }
+            }
+        }
+
+        // The reveals line the inside of each opening through the thickness
+        // of the wall.
+        for outline in &outlines {
+            let clamped: Vec<Vec2> = outline
+                .iter()
+                .map(|corner| {
+                    Vec2::new(
+                        corner.x.clamp(-half_length, half_length),
+                        corner.y.clamp(0.0, height),
+                    )
+                })
+                .collect();
+            for (a, b) in clamped.iter().zip(clamped.iter().cycle().skip(1)) {
+                let on_floor = a.y.max(b.y) <= WALL_GEOMETRY_EPSILON;
+                let on_end = a.x == b.x && a.x.abs() >= half_length - WALL_GEOMETRY_EPSILON;
+                if on_floor || on_end || a.distance(*b) <= WALL_GEOMETRY_EPSILON {
+                    continue;
+                }
+                let inward = (*b - *a).perp().normalize();
+                self.quad(
+                    [
+                        point(a.x, a.y, -half_thickness),
+                        point(b.x, b.y, -half_thickness),
+                        point(b.x, b.y, half_thickness),
+                        point(a.x, a.y, half_thickness),
+                    ],
+                    along * inward.x + Vec3::Y * inward.y,
+                );
+            }
+        }
+    }
+
+    // Adds a panel that fills an opening of the given shape, centred on the
+    // opening and facing along Z, with `size` as its width, height and
+    // thickness. Its edges sit against the reveals of the wall around it, so
+    // only the two faces are built.
+    pub fn add_panel(&mut self, shape: &DoorShape, size: Vec3) {
+        let (width, height) = (size.x, size.y);
+        let outline = shape.outline(width, height);
+        let half_thickness = size.z / 2.0;
+        let point = |x: f32, y: f32, z: f32| Vec3::new(x, y - height / 2.0, z);
+
+        let mut cuts: Vec<f32> = outline.iter().map(|corner| corner.x).collect();
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup_by(|a, b| (*a - *b).abs() < WALL_GEOMETRY_EPSILON);
+
+        for strip in cuts.windows(2) {
+            let (start, end) = (strip[0], strip[1]);
+            let Some(span) = span(&outline, start, end, height) else {
+                continue;
+            };
+            let face = |z: f32| {
+                [
+                    point(start, span.bottom.0, z),
+                    point(end, span.bottom.1, z),
+                    point(end, span.top.1, z),
+                    point(start, span.top.0, z),
+                ]
+            };
+            let (front, back) = (face(half_thickness), face(-half_thickness));
+            self.quad(front, Vec3::Z);
+            self.quad(back, Vec3::NEG_Z);
+
+            let corners: Vec<Vec3> = front.into_iter().chain(back).collect();
+            if let Some(collider) = Collider::convex_hull(&corners) {
+                self.colliders.push((Vec3::ZERO, Quat::IDENTITY, collider));
+            }
+        }
+    }
+
+    pub fn build(self) -> Option<(Mesh, Collider)> {
+        if self.indices.is_empty() {
+            return None;
+        }
+
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+        .with_inserted_indices(Indices::U32(self.indices))
+        .with_generated_tangents()
+        .expect("walls have positions, normals and uvs");
+        Some((mesh, Collider::compound(self.colliders)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arched_panel_stays_inside_the_arch() {
+        let (width, height) = (6.0, 6.0);
+        let mut panel = WallMeshBuilder::default();
+        panel.add_panel(&DoorShape::Arch, Vec3::new(width, height, WALL_THICKNESS));
+
+        // The arch springs from half way up, so the panel must not reach the
+        // wall left in the top corners of the opening's bounding box.
+        let half_width = width / 2.0;
+        let spring = height - half_width;
+        for [x, y, _] in &panel.positions {
+            let y = y + height / 2.0;
+            let limit = spring + (half_width * half_width - x * x).max(0.0).sqrt();
+            assert!(
+                y <= limit + WALL_GEOMETRY_EPSILON,
+                "({x}, {y}) is outside the arch"
+            );
+        }
+        assert!(panel.build().is_some());
+    }
+}
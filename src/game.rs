@@ -1,49 +1,198 @@
-use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy_rapier3d::plugin::PhysicsSet;
 
 use super::game_state::GameState;
-use crate::entities::map::map::MapEntity;
-use crate::entities::map::{despawn_map, setup_map};
+use crate::entities::map::door::{animate_doors, apply_door_commands, open_doors_near_player};
+use crate::entities::map::generator::DungeonSeed;
+use crate::entities::map::hazard::{check_kill_plane, track_safe_position, update_hazards};
+use crate::entities::map::level::{Level, LevelHandle};
+use crate::entities::map::lighting::{
+    apply_room_atmosphere, cull_room_lights, flicker_torches, reset_atmosphere,
+};
+use crate::entities::map::map::{MapEntity, RoomBoundsData};
+use crate::entities::map::mechanism::{
+    Mechanisms, evaluate_mechanisms, read_pressure_plates, strike_sword_switches,
+    update_mechanism_parts, use_levers,
+};
+use crate::entities::map::platform::{interpolate_platforms, move_platforms, ride_platforms};
+use crate::entities::map::room_tracking::{reset_current_room, track_player_room};
+use crate::entities::map::secret::{break_secret_walls, expire_wall_fragments, record_secrets};
+use crate::entities::map::streaming::stream_rooms;
+use crate::entities::map::trigger::LastCheckpoint;
+use crate::entities::map::trigger::{apply_trigger_actions, detect_triggers};
+use crate::entities::map::{despawn_map, load_map, setup_map};
+use crate::input::update_action_axes;
 use crate::menu::load_menu_assets;
+use crate::player::health::{
+    DamageEvent, DeathEvent, apply_damage, handle_player_death, tick_invulnerability,
+};
+use crate::player::interpolation::{interpolate_camera, record_physics_translation};
 use crate::player::player::Player;
-use crate::player::weapons::{animate_sword_slash, spawn_weapons};
+use crate::player::stamina::regenerate_stamina;
+use crate::player::weapons::{
+    SwordStrike, animate_sword_slash, emit_sword_strikes, spawn_weapons, start_sword_slash,
+};
 use crate::player::{
-    LookInput, MovementInput, despawn_player, handle_input, player_look, player_movement,
-    setup_player,
+    CombatInput, LookInput, MovementInput, despawn_player, dodge_roll, handle_input, player_look,
+    player_movement, setup_player,
 };
 use crate::ui::cross_hair::Crosshair;
-use crate::ui::hud::{HUD, cleanup_hud, setup_hud};
+use crate::ui::hud::{
+    HUD, cleanup_hud, setup_hud, update_health_display, update_prompt_display,
+    update_room_name_display, update_secret_display, update_stamina_display,
+};
 use crate::ui::{
     EscButtonState, PauseOverlay, PauseState, despawn_crosshair, despawn_pause_ui, hide_cursor,
     show_cursor, spawn_crosshair, update_esc_button_border,
 };
 
 pub fn game_plugin(app: &mut App) {
-    app.init_resource::<MovementInput>()
+    app.add_event::<DamageEvent>()
+        .add_event::<DeathEvent>()
+        .add_event::<SwordStrike>()
+        .init_resource::<MovementInput>()
         .init_resource::<LookInput>()
+        .init_resource::<CombatInput>()
         .init_resource::<PauseState>()
         .init_resource::<EscButtonState>()
         .add_systems(OnEnter(GameState::Game), game_setup)
         .add_systems(
             PreUpdate,
             handle_input
-                .after(InputSystem)
+                .after(update_action_axes)
+                .run_if(in_state(GameState::Game)),
+        )
+        .add_systems(
+            Update,
+            map_setup.run_if(in_state(GameState::Game).and(not(resource_exists::<RoomBoundsData>))),
+        )
+        .add_systems(
+            Update,
+            (
+                detect_triggers,
+                track_player_room,
+                (
+                    apply_trigger_actions,
+                    update_prompt_display,
+                    update_room_name_display,
+                    apply_room_atmosphere,
+                    cull_room_lights,
+                    stream_rooms,
+                ),
+            )
+                .chain()
+                .run_if(in_state(GameState::Game).and(resource_exists::<RoomBoundsData>)),
+        )
+        .add_systems(
+            Update,
+            (
+                update_hazards,
+                track_safe_position,
+                check_kill_plane,
+                tick_invulnerability,
+                apply_damage,
+                handle_player_death,
+                update_health_display,
+            )
+                .chain()
+                .after(detect_triggers)
+                .run_if(
+                    in_state(GameState::Game)
+                        .and(not_paused)
+                        .and(resource_exists::<RoomBoundsData>),
+                ),
+        )
+        .add_systems(
+            Update,
+            (
+                emit_sword_strikes,
+                read_pressure_plates,
+                use_levers,
+                strike_sword_switches,
+                evaluate_mechanisms,
+                update_mechanism_parts,
+            )
+                .chain()
+                .after(detect_triggers)
+                .before(apply_door_commands)
+                .run_if(
+                    in_state(GameState::Game)
+                        .and(not_paused)
+                        .and(resource_exists::<Mechanisms>),
+                ),
+        )
+        .add_systems(
+            Update,
+            (
+                break_secret_walls,
+                record_secrets,
+                update_secret_display,
+                expire_wall_fragments,
+            )
+                .chain()
+                .after(emit_sword_strikes)
+                .run_if(
+                    in_state(GameState::Game)
+                        .and(not_paused)
+                        .and(resource_exists::<RoomBoundsData>),
+                ),
+        )
+        .add_systems(Update, flicker_torches.run_if(in_state(GameState::Game)))
+        .add_systems(
+            Update,
+            (player_look, interpolate_camera, interpolate_platforms)
+                .chain()
+                .run_if(in_state(GameState::Game)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (move_platforms, ride_platforms, player_movement, dodge_roll)
+                .chain()
+                .before(PhysicsSet::SyncBackend)
+                .run_if(
+                    in_state(GameState::Game)
+                        .and(not_paused)
+                        .and(resource_exists::<RoomBoundsData>),
+                ),
+        )
+        .add_systems(
+            FixedUpdate,
+            record_physics_translation
+                .after(PhysicsSet::Writeback)
                 .run_if(in_state(GameState::Game)),
         )
-        .add_systems(Update, player_look.run_if(in_state(GameState::Game)))
         .add_systems(
             Update,
-            player_movement.run_if(in_state(GameState::Game).and(not_paused)),
+            (regenerate_stamina, update_stamina_display).chain().run_if(
+                in_state(GameState::Game)
+                    .and(not_paused)
+                    .and(resource_exists::<RoomBoundsData>),
+            ),
+        )
+        .add_systems(
+            Update,
+            start_sword_slash
+                .before(emit_sword_strikes)
+                .run_if(in_state(GameState::Game).and(not_paused)),
         )
         .add_systems(
             Update,
             animate_sword_slash.run_if(in_state(GameState::Game).and(not_paused)),
         )
+        .add_systems(
+            Update,
+            (apply_door_commands, open_doors_near_player, animate_doors)
+                .chain()
+                .run_if(in_state(GameState::Game).and(not_paused)),
+        )
         .add_systems(
             Update,
             update_esc_button_border.run_if(in_state(GameState::Game).and(paused)),
         )
-        .add_systems(OnExit(GameState::Game), game_cleanup);
+        .add_systems(
+            OnExit(GameState::Game),
+            (game_cleanup, reset_current_room, reset_atmosphere),
+        );
 }
 
 pub fn not_paused(pause_state: Res<PauseState>) -> bool {
@@ -56,21 +205,46 @@ pub fn paused(pause_state: Res<PauseState>) -> bool {
 
 fn game_setup(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
+    mut levels: ResMut<Assets<Level>>,
+    seed: Option<Res<DungeonSeed>>,
     windows: Query<&mut Window>,
 ) {
     load_menu_assets(&mut commands, &asset_server);
+    commands.insert_resource(LastCheckpoint::default());
+    commands.insert_resource(MovementInput::default());
+    commands.insert_resource(CombatInput::default());
 
     let camera_entity = setup_player(&mut commands);
-    setup_map(&mut commands, &mut meshes, &mut materials, &asset_server);
+    load_map(&mut commands, &asset_server, &mut levels, seed.as_deref());
     spawn_weapons(&mut commands, &asset_server, &camera_entity);
     setup_hud(&mut commands, &windows);
     hide_cursor(windows);
     spawn_crosshair(&mut commands, &asset_server, &camera_entity);
 }
 
+fn map_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    level_handle: Res<LevelHandle>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.get(&level_handle.0) else {
+        return;
+    };
+
+    setup_map(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &asset_server,
+        &level_handle,
+        level,
+    );
+}
+
 fn game_cleanup(
     mut commands: Commands,
     query_map: Query<Entity, With<MapEntity>>,
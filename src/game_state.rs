@@ -6,4 +6,6 @@ pub enum GameState {
     Splash,
     Menu,
     Game,
+    GameOver,
+    Editor,
 }
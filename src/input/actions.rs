@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
+
+use crate::{
+    constants::{
+        input::{
+            GAMEPAD_LOOK_BOOST, GAMEPAD_LOOK_BOOST_THRESHOLD, GAMEPAD_LOOK_BOOST_TIME,
+            GAMEPAD_LOOK_DEADZONE, GAMEPAD_LOOK_EXPONENT, GAMEPAD_LOOK_SENSITIVITY,
+            GAMEPAD_MOVE_DEADZONE,
+        },
+        player::MOUSE_SENSITIVITY,
+    },
+    input::bindings::{Action, Binding, InputBindings},
+};
+
+// Gameplay reads actions from here instead of from specific keys or buttons.
+// `movement` is x right and y forward, at most 1 long. `look` is this
+// frame's turn in degrees, positive to the right and down like the mouse.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    movement: Vec2,
+    look: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    pub fn movement(&self) -> Vec2 {
+        self.movement
+    }
+
+    pub fn look(&self) -> Vec2 {
+        self.look
+    }
+
+    fn update(&mut self, held: HashSet<Action>) {
+        self.just_pressed = held.difference(&self.pressed).copied().collect();
+        self.just_released = self.pressed.difference(&held).copied().collect();
+        self.pressed = held;
+    }
+}
+
+pub fn update_action_state(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<InputBindings>,
+    mut actions: ResMut<ActionState>,
+) {
+    let held = Action::all()
+        .iter()
+        .copied()
+        .filter(|action| {
+            let bound = match bindings.get(*action) {
+                Binding::Key(key) => keyboard.pressed(key),
+                Binding::Mouse(button) => mouse.pressed(button),
+            };
+            bound
+                || action
+                    .gamepad_button()
+                    .is_some_and(|button| gamepads.iter().any(|pad| pad.pressed(button)))
+        })
+        .collect();
+    actions.update(held);
+}
+
+pub fn update_action_axes(
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    gamepads: Query<&Gamepad>,
+    time: Res<Time>,
+    mut actions: ResMut<ActionState>,
+    mut look_boost_time: Local<f32>,
+) {
+    let axis = |positive: Action, negative: Action| match (
+        actions.pressed(positive),
+        actions.pressed(negative),
+    ) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    };
+    let keys = Vec2::new(
+        axis(Action::MoveRight, Action::MoveLeft),
+        axis(Action::MoveForward, Action::MoveBack),
+    );
+    let stick = strongest(
+        gamepads.iter().map(Gamepad::left_stick),
+        GAMEPAD_MOVE_DEADZONE,
+    );
+    actions.movement = if stick == Vec2::ZERO {
+        keys.normalize_or_zero()
+    } else {
+        stick
+    };
+
+    // Small deflections are eased by the exponent for fine aim; holding the
+    // stick near the edge ramps the speed up for quick turns.
+    let stick = strongest(
+        gamepads.iter().map(Gamepad::right_stick),
+        GAMEPAD_LOOK_DEADZONE,
+    );
+    if stick.length() >= GAMEPAD_LOOK_BOOST_THRESHOLD {
+        *look_boost_time = (*look_boost_time + time.delta_secs()).min(GAMEPAD_LOOK_BOOST_TIME);
+    } else {
+        *look_boost_time = 0.0;
+    }
+    let boost = 1.0 + (GAMEPAD_LOOK_BOOST - 1.0) * *look_boost_time / GAMEPAD_LOOK_BOOST_TIME;
+    let curved = stick.normalize_or_zero() * stick.length().powf(GAMEPAD_LOOK_EXPONENT);
+    actions.look = mouse_motion.delta * MOUSE_SENSITIVITY
+        + Vec2::new(curved.x, -curved.y) * GAMEPAD_LOOK_SENSITIVITY * boost * time.delta_secs();
+}
+
+// Picks the most deflected stick and rescales it so movement starts from
+// zero at the edge of the deadzone.
+fn strongest(sticks: impl Iterator<Item = Vec2>, deadzone: f32) -> Vec2 {
+    let stick = sticks
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default()
+        .clamp_length_max(1.0);
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+    stick / length * (length - deadzone) / (1.0 - deadzone)
+}
@@ -0,0 +1,207 @@
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+    Dodge,
+    Attack,
+    Interact,
+    Pause,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+// Bindings are saved by action name, so a config file written by an older
+// build only needs the actions it knows about; the rest keep their defaults.
+#[derive(Resource, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct InputBindings(BTreeMap<Action, Binding>);
+
+#[derive(Debug, Error)]
+pub enum BindingsError {
+    #[error("could not read controls file {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("{path}:{source}")]
+    Parse {
+        path: String,
+        source: ron::error::SpannedError,
+    },
+    #[error("could not serialize controls: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not write controls file {path}: {source}")]
+    Write {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+impl Action {
+    pub fn all() -> &'static [Action] {
+        &[
+            Action::MoveForward,
+            Action::MoveBack,
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::Jump,
+            Action::Sprint,
+            Action::Dodge,
+            Action::Attack,
+            Action::Interact,
+            Action::Pause,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move Forward",
+            Action::MoveBack => "Move Back",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Jump => "Jump",
+            Action::Sprint => "Sprint",
+            Action::Dodge => "Dodge",
+            Action::Attack => "Attack",
+            Action::Interact => "Interact",
+            Action::Pause => "Pause",
+        }
+    }
+
+    // The gamepad layout is fixed; movement comes from the left stick.
+    pub fn gamepad_button(&self) -> Option<GamepadButton> {
+        match self {
+            Action::Jump => Some(GamepadButton::South),
+            Action::Sprint => Some(GamepadButton::LeftThumb),
+            Action::Dodge => Some(GamepadButton::East),
+            Action::Attack => Some(GamepadButton::RightTrigger2),
+            Action::Interact => Some(GamepadButton::West),
+            Action::Pause => Some(GamepadButton::Start),
+            Action::MoveForward | Action::MoveBack | Action::MoveLeft | Action::MoveRight => None,
+        }
+    }
+
+    fn default_binding(&self) -> Binding {
+        match self {
+            Action::MoveForward => Binding::Key(KeyCode::KeyW),
+            Action::MoveBack => Binding::Key(KeyCode::KeyS),
+            Action::MoveLeft => Binding::Key(KeyCode::KeyA),
+            Action::MoveRight => Binding::Key(KeyCode::KeyD),
+            Action::Jump => Binding::Key(KeyCode::Space),
+            Action::Sprint => Binding::Key(KeyCode::ShiftLeft),
+            Action::Dodge => Binding::Key(KeyCode::ControlLeft),
+            Action::Attack => Binding::Mouse(MouseButton::Left),
+            Action::Interact => Binding::Key(KeyCode::KeyE),
+            Action::Pause => Binding::Key(KeyCode::Escape),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or(name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                f.write_str(name)
+            }
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(
+            Action::all()
+                .iter()
+                .map(|action| (*action, action.default_binding()))
+                .collect(),
+        )
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> Binding {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn action_for(&self, binding: Binding) -> Option<Action> {
+        Action::all()
+            .iter()
+            .copied()
+            .find(|action| self.get(*action) == binding)
+    }
+
+    // Binds the action, handing its old binding to any action that already
+    // used the new one so no two actions ever share an input. Returns the
+    // action that was moved.
+    pub fn rebind(&mut self, action: Action, binding: Binding) -> Option<Action> {
+        let previous = self.get(action);
+        let conflict = self.action_for(binding).filter(|other| *other != action);
+        if let Some(other) = conflict {
+            self.0.insert(other, previous);
+        }
+        self.0.insert(action, binding);
+        conflict
+    }
+
+    // Replaces action names in braces, like `{Jump}`, with their bindings.
+    pub fn fill_prompt(&self, text: &str) -> String {
+        Action::all().iter().fold(text.to_string(), |text, action| {
+            text.replace(&format!("{{{action:?}}}"), &self.get(*action).to_string())
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BindingsError> {
+        let text = std::fs::read_to_string(path).map_err(|source| BindingsError::Read {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let loaded: InputBindings =
+            ron::from_str(&text).map_err(|source| BindingsError::Parse {
+                path: path.display().to_string(),
+                source,
+            })?;
+
+        let mut bindings = InputBindings::default();
+        for (action, binding) in loaded.0 {
+            bindings.rebind(action, binding);
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BindingsError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|source| BindingsError::Write {
+                path: path.display().to_string(),
+                source,
+            })?;
+        }
+        std::fs::write(path, text + "\n").map_err(|source| BindingsError::Write {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+}
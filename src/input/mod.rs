@@ -0,0 +1,33 @@
+pub mod actions;
+pub mod bindings;
+
+use std::{io::ErrorKind, path::Path};
+
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::constants::input::CONTROLS_CONFIG_PATH;
+
+pub use actions::{ActionState, update_action_axes, update_action_state};
+pub use bindings::{Action, Binding, BindingsError, InputBindings};
+
+pub fn input_plugin(app: &mut App) {
+    let bindings = match InputBindings::load(Path::new(CONTROLS_CONFIG_PATH)) {
+        Ok(bindings) => bindings,
+        Err(BindingsError::Read { source, .. }) if source.kind() == ErrorKind::NotFound => {
+            InputBindings::default()
+        }
+        Err(error) => {
+            warn!("{error}, using default controls");
+            InputBindings::default()
+        }
+    };
+
+    app.insert_resource(bindings)
+        .init_resource::<ActionState>()
+        .add_systems(
+            PreUpdate,
+            (update_action_state, update_action_axes)
+                .chain()
+                .after(InputSystem),
+        );
+}
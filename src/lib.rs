@@ -0,0 +1,10 @@
+pub mod constants;
+pub mod despawn_screen;
+pub mod editor;
+pub mod entities;
+pub mod game;
+pub mod game_state;
+pub mod input;
+pub mod menu;
+pub mod player;
+pub mod ui;
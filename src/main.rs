@@ -1,23 +1,53 @@
 use bevy::prelude::*;
 
-mod constants;
-mod despawn_screen;
-mod entities;
-mod game;
-mod game_state;
-mod menu;
-mod player;
-mod ui;
-
-use bevy_rapier3d::plugin::{NoUserData, RapierPhysicsPlugin};
-use game::game_plugin;
-use game_state::GameState;
-use menu::{menu_plugin, splash_plugin};
+use bevy_rapier3d::plugin::{NoUserData, RapierPhysicsPlugin, TimestepMode};
+use dark_city::constants::physics::PHYSICS_TICK_RATE;
+use dark_city::editor::editor_plugin;
+use dark_city::entities::map::{generator::DungeonSeed, map_plugin};
+use dark_city::game::game_plugin;
+use dark_city::game_state::GameState;
+use dark_city::input::input_plugin;
+use dark_city::menu::{
+    controls_plugin, game_over_plugin, menu_plugin, navigation_plugin, splash_plugin,
+};
 
 fn main() {
-    App::new()
-        .add_plugins((DefaultPlugins, RapierPhysicsPlugin::<NoUserData>::default()))
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+        .insert_resource(Time::<Fixed>::from_hz(PHYSICS_TICK_RATE))
+        .insert_resource(TimestepMode::Fixed {
+            dt: (1.0 / PHYSICS_TICK_RATE) as f32,
+            substeps: 1,
+        })
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule())
         .init_state::<GameState>()
-        .add_plugins((splash_plugin, menu_plugin, game_plugin))
-        .run();
+        .add_plugins((
+            splash_plugin,
+            menu_plugin,
+            controls_plugin,
+            navigation_plugin,
+            game_over_plugin,
+            input_plugin,
+            map_plugin,
+            game_plugin,
+            editor_plugin,
+        ));
+
+    if let Some(seed) = seed_from_args() {
+        app.insert_resource(DungeonSeed(seed));
+    }
+
+    app.run();
+}
+
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    let value = args.next()?;
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            warn!("Ignoring invalid --seed value `{value}`");
+            None
+        }
+    }
 }
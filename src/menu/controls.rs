@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::{input::CONTROLS_CONFIG_PATH, text::MENU_TEXT_COLOR},
+    despawn_screen::despawn_screen,
+    input::{Action, Binding, InputBindings},
+    menu::{
+        menu::{MenuState, NORMAL_BUTTON},
+        navigation::BackButton,
+    },
+};
+
+pub fn controls_plugin(app: &mut App) {
+    app.init_resource::<Rebinding>()
+        .add_systems(OnEnter(MenuState::Controls), controls_setup)
+        .add_systems(
+            Update,
+            (controls_action, capture_binding, update_binding_texts)
+                .chain()
+                .run_if(in_state(MenuState::Controls)),
+        )
+        .add_systems(
+            OnExit(MenuState::Controls),
+            (despawn_screen::<OnControlsScreen>, stop_rebinding),
+        );
+}
+
+#[derive(Component)]
+struct OnControlsScreen;
+
+#[derive(Component)]
+enum ControlsButtonAction {
+    Rebind(Action),
+    Reset,
+    Back,
+}
+
+#[derive(Component)]
+struct BindingText(Action);
+
+#[derive(Component)]
+struct StatusText;
+
+// The click that starts a rebind is still "just pressed" on that frame, so
+// capturing only begins once the wait is armed on the following frame.
+#[derive(Resource, Default, PartialEq)]
+enum Rebinding {
+    #[default]
+    Idle,
+    Waiting {
+        action: Action,
+        armed: bool,
+    },
+}
+
+fn controls_setup(mut commands: Commands, windows: Query<&Window>, bindings: Res<InputBindings>) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let text_font = TextFont {
+        font_size: window.height() * 0.03,
+        ..default()
+    };
+    let button_node = Node {
+        width: Val::Percent(40.0),
+        height: Val::Percent(90.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let row_node = Node {
+        width: Val::Percent(100.0),
+        height: Val::Percent(6.0),
+        justify_content: JustifyContent::SpaceBetween,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button = |action: ControlsButtonAction, label: &str| {
+        (
+            Button,
+            Node {
+                width: Val::Percent(45.0),
+                height: Val::Percent(8.0),
+                margin: UiRect::top(Val::Percent(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            action,
+            children![(
+                Text::new(label),
+                text_font.clone(),
+                TextColor(MENU_TEXT_COLOR),
+            )],
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnControlsScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        height: Val::Percent(90.0),
+                        width: Val::Percent(50.0),
+                        padding: UiRect::all(Val::Percent(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.5)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Controls"),
+                        TextFont {
+                            font_size: window.height() * 0.07,
+                            ..default()
+                        },
+                        TextColor(MENU_TEXT_COLOR),
+                        Node {
+                            margin: UiRect::bottom(Val::Percent(2.0)),
+                            ..default()
+                        },
+                    ));
+                    for action in Action::all() {
+                        parent.spawn((
+                            row_node.clone(),
+                            children![
+                                (
+                                    Text::new(action.label()),
+                                    text_font.clone(),
+                                    TextColor(MENU_TEXT_COLOR),
+                                ),
+                                (
+                                    Button,
+                                    button_node.clone(),
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    ControlsButtonAction::Rebind(*action),
+                                    children![(
+                                        Text::new(bindings.get(*action).to_string()),
+                                        text_font.clone(),
+                                        TextColor(MENU_TEXT_COLOR),
+                                        BindingText(*action),
+                                    )],
+                                ),
+                            ],
+                        ));
+                    }
+                    parent.spawn((
+                        Text::default(),
+                        text_font.clone(),
+                        TextColor(MENU_TEXT_COLOR),
+                        Node {
+                            margin: UiRect::top(Val::Percent(2.0)),
+                            ..default()
+                        },
+                        StatusText,
+                    ));
+                    parent.spawn(button(ControlsButtonAction::Reset, "Reset to Defaults"));
+                    parent
+                        .spawn(button(ControlsButtonAction::Back, "Back"))
+                        .insert(BackButton);
+                });
+        });
+}
+
+fn controls_action(
+    interaction_query: Query<(&Interaction, &ControlsButtonAction), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut status: Query<&mut Text, With<StatusText>>,
+) {
+    if *rebinding != Rebinding::Idle {
+        return;
+    }
+
+    for (interaction, action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let message = match action {
+            ControlsButtonAction::Rebind(action) => {
+                *rebinding = Rebinding::Waiting {
+                    action: *action,
+                    armed: false,
+                };
+                format!("Press a key or mouse button for {}", action.label())
+            }
+            ControlsButtonAction::Reset => {
+                *bindings = InputBindings::default();
+                save_message(&bindings, "Controls reset to defaults".to_string())
+            }
+            ControlsButtonAction::Back => {
+                menu_state.set(MenuState::Main);
+                return;
+            }
+        };
+        if let Ok(mut text) = status.single_mut() {
+            text.0 = message;
+        }
+    }
+}
+
+fn capture_binding(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut status: Query<&mut Text, With<StatusText>>,
+) {
+    let Rebinding::Waiting { action, armed } = *rebinding else {
+        return;
+    };
+    if !armed {
+        *rebinding = Rebinding::Waiting {
+            action,
+            armed: true,
+        };
+        return;
+    }
+
+    let binding = keyboard
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        });
+    let gamepad_back = gamepads
+        .iter()
+        .any(|pad| pad.just_pressed(GamepadButton::East));
+    if binding.is_none() && !gamepad_back {
+        return;
+    }
+    *rebinding = Rebinding::Idle;
+
+    // Escape and the gamepad's back button cancel, unless Escape is what the
+    // pause action is being bound to.
+    let message = if let Some(binding) = binding
+        && (binding != Binding::Key(KeyCode::Escape) || action == Action::Pause)
+    {
+        let mut message = format!("{} bound to {binding}", action.label());
+        if let Some(moved) = bindings.rebind(action, binding) {
+            message += &format!(", {} moved to {}", moved.label(), bindings.get(moved));
+        }
+        save_message(&bindings, message)
+    } else {
+        "Cancelled".to_string()
+    };
+    if let Ok(mut text) = status.single_mut() {
+        text.0 = message;
+    }
+}
+
+fn save_message(bindings: &InputBindings, message: String) -> String {
+    match bindings.save(Path::new(CONTROLS_CONFIG_PATH)) {
+        Ok(()) => message,
+        Err(error) => {
+            warn!("{error}");
+            format!("{message} (not saved: {error})")
+        }
+    }
+}
+
+fn update_binding_texts(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    mut texts: Query<(&mut Text, &BindingText)>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (mut text, BindingText(action)) in &mut texts {
+        text.0 = match *rebinding {
+            Rebinding::Waiting {
+                action: waiting, ..
+            } if waiting == *action => "...".to_string(),
+            _ => bindings.get(*action).to_string(),
+        };
+    }
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    *rebinding = Rebinding::Idle;
+}
@@ -0,0 +1,115 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    constants::text::MENU_TEXT_COLOR,
+    despawn_screen::despawn_screen,
+    game_state::GameState,
+    menu::{
+        camera2d_despawn, camera2d_spawn,
+        menu::{NORMAL_BUTTON, button_system},
+    },
+};
+
+pub fn game_over_plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::GameOver),
+        (camera2d_spawn, game_over_setup),
+    )
+    .add_systems(
+        Update,
+        (game_over_action, button_system).run_if(in_state(GameState::GameOver)),
+    )
+    .add_systems(
+        OnExit(GameState::GameOver),
+        (despawn_screen::<OnGameOverScreen>, camera2d_despawn),
+    );
+}
+
+#[derive(Component)]
+struct OnGameOverScreen;
+
+#[derive(Component)]
+enum GameOverButtonAction {
+    Retry,
+    MainMenu,
+    Quit,
+}
+
+fn game_over_setup(mut commands: Commands, windows: Query<&Window>) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let button_node = Node {
+        width: Val::Percent(60.0),
+        height: Val::Percent(12.0),
+        margin: UiRect::all(Val::Percent(3.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_font = TextFont {
+        font_size: window.height() * 0.045,
+        ..default()
+    };
+    let button = |action: GameOverButtonAction, label: &str| {
+        (
+            Button,
+            button_node.clone(),
+            BackgroundColor(NORMAL_BUTTON),
+            action,
+            children![(
+                Text::new(label),
+                button_text_font.clone(),
+                TextColor(MENU_TEXT_COLOR),
+            )],
+        )
+    };
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.08, 0.0, 0.0)),
+        OnGameOverScreen,
+        children![
+            (
+                Text::new("You Died"),
+                TextFont {
+                    font_size: window.height() * 0.12,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.1, 0.1)),
+                Node {
+                    margin: UiRect::bottom(Val::Percent(4.0)),
+                    ..default()
+                },
+            ),
+            button(GameOverButtonAction::Retry, "Retry"),
+            button(GameOverButtonAction::MainMenu, "Main Menu"),
+            button(GameOverButtonAction::Quit, "Quit"),
+        ],
+    ));
+}
+
+fn game_over_action(
+    interaction_query: Query<(&Interaction, &GameOverButtonAction), Changed<Interaction>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match action {
+                GameOverButtonAction::Retry => game_state.set(GameState::Game),
+                GameOverButtonAction::MainMenu => game_state.set(GameState::Menu),
+                GameOverButtonAction::Quit => {
+                    app_exit_events.write(AppExit::Success);
+                }
+            }
+        }
+    }
+}
@@ -18,21 +18,22 @@ pub fn menu_plugin(app: &mut App) {
     app.init_state::<MenuState>()
         .add_systems(OnEnter(GameState::Menu), camera2d_spawn)
         .add_systems(OnEnter(GameState::Menu), menu_setup)
+        .add_systems(OnEnter(GameState::Menu), spawn_clouds)
         .add_systems(OnEnter(MenuState::Main), main_menu_setup)
-        .add_systems(OnEnter(MenuState::Main), spawn_clouds)
         .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
-        .add_systems(OnExit(MenuState::Main), despawn_clouds)
-        .add_systems(OnExit(MenuState::Main), cleanup_assets)
+        .add_systems(OnExit(GameState::Menu), despawn_clouds)
+        .add_systems(OnExit(GameState::Menu), cleanup_assets)
         .add_systems(
             Update,
             (menu_action, button_system, animate_clouds).run_if(in_state(GameState::Menu)),
         )
-        .add_systems(OnExit(MenuState::Main), camera2d_despawn);
+        .add_systems(OnExit(GameState::Menu), camera2d_despawn);
 }
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum MenuState {
     Main,
+    Controls,
     #[default]
     Disabled,
 }
@@ -40,21 +41,23 @@ pub enum MenuState {
 #[derive(Component)]
 struct OnMainMenuScreen;
 
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const HOVERED_PRESSED_BUTTON: Color = Color::srgb(0.25, 0.65, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
 
 #[derive(Component)]
-struct SelectedOption;
+pub struct SelectedOption;
 
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    Editor,
+    Controls,
     Quit,
 }
 
-fn button_system(
+pub fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
         (Changed<Interaction>, With<Button>),
@@ -79,7 +82,7 @@ fn main_menu_setup(mut commands: Commands, windows: Query<&Window>) {
     let button_node = Node {
         width: Val::Percent(40.0),
         height: Val::Percent(10.0),
-        margin: UiRect::all(Val::Percent(5.0)),
+        margin: UiRect::all(Val::Percent(4.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         top: Val::Percent(10.0),
@@ -103,7 +106,7 @@ fn main_menu_setup(mut commands: Commands, windows: Query<&Window>) {
             Node {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
-                height: Val::Percent(50.0),
+                height: Val::Percent(60.0),
                 width: Val::Percent(40.0),
                 ..default()
             },
@@ -133,6 +136,28 @@ fn main_menu_setup(mut commands: Commands, windows: Query<&Window>) {
                         TextColor(MENU_TEXT_COLOR),
                     ),]
                 ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::Editor,
+                    children![(
+                        Text::new("Level Editor"),
+                        button_text_font.clone(),
+                        TextColor(MENU_TEXT_COLOR),
+                    ),]
+                ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::Controls,
+                    children![(
+                        Text::new("Controls"),
+                        button_text_font.clone(),
+                        TextColor(MENU_TEXT_COLOR),
+                    ),]
+                ),
                 (
                     Button,
                     button_node,
@@ -168,6 +193,13 @@ fn menu_action(
                     game_state.set(GameState::Game);
                     menu_state.set(MenuState::Disabled);
                 }
+                MenuButtonAction::Editor => {
+                    game_state.set(GameState::Editor);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Controls => {
+                    menu_state.set(MenuState::Controls);
+                }
             }
         }
     }
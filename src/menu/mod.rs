@@ -1,10 +1,16 @@
 pub mod camera2d;
+pub mod controls;
+pub mod game_over;
 pub mod menu;
 mod menu_cloud;
+pub mod navigation;
 pub mod splash;
 
 pub use camera2d::{camera2d_despawn, camera2d_spawn};
+pub use controls::controls_plugin;
+pub use game_over::game_over_plugin;
 pub use menu::menu_plugin;
+pub use navigation::navigation_plugin;
 pub use splash::splash_plugin;
 
 pub use menu::{MenuAssets, are_menu_assets_loaded, load_menu_assets};
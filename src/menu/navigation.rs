@@ -0,0 +1,164 @@
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*, ui::UiSystem};
+
+use crate::constants::input::{MENU_REPEAT_DELAY, MENU_REPEAT_INTERVAL, MENU_STICK_THRESHOLD};
+
+// Gamepad focus drives the same `Interaction` the mouse does, so every menu's
+// hover colours and button actions work without knowing about gamepads.
+pub fn navigation_plugin(app: &mut App) {
+    app.init_resource::<MenuFocus>()
+        .add_systems(PreUpdate, navigate_menus.after(UiSystem::Focus));
+}
+
+// Pressed by the gamepad's back button on screens that have one.
+#[derive(Component)]
+pub struct BackButton;
+
+#[derive(Resource, Default)]
+struct MenuFocus {
+    focused: Option<Entity>,
+    pressed: Option<Entity>,
+    direction: Vec2,
+    repeat: f32,
+}
+
+fn navigate_menus(
+    gamepads: Query<&Gamepad>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    time: Res<Time>,
+    mut focus: ResMut<MenuFocus>,
+    mut buttons: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &InheritedVisibility,
+            &mut Interaction,
+        ),
+        With<Button>,
+    >,
+    back_buttons: Query<(), With<BackButton>>,
+) {
+    // A press only lasts one frame, like a mouse click and release.
+    if let Some(entity) = focus.pressed.take()
+        && let Ok((_, _, _, mut interaction)) = buttons.get_mut(entity)
+    {
+        interaction.set_if_neq(Interaction::None);
+    }
+    if mouse_motion.delta != Vec2::ZERO {
+        focus.focused = None;
+    }
+    let visible = |entity: Entity| {
+        buttons
+            .get(entity)
+            .is_ok_and(|(_, _, visibility, _)| visibility.get())
+    };
+    if focus.focused.is_some_and(|entity| !visible(entity)) {
+        focus.focused = None;
+    }
+
+    let direction = gamepads
+        .iter()
+        .map(|pad| {
+            let stick = pad.left_stick();
+            let stick = if stick.length() >= MENU_STICK_THRESHOLD {
+                stick
+            } else {
+                Vec2::ZERO
+            };
+            pad.dpad() + stick
+        })
+        .find(|direction| *direction != Vec2::ZERO)
+        .map(snap_to_axis)
+        .unwrap_or_default();
+    let mut step = None;
+    if direction != focus.direction {
+        focus.repeat = MENU_REPEAT_DELAY;
+        step = Some(direction);
+    } else if direction != Vec2::ZERO {
+        focus.repeat -= time.delta_secs();
+        if focus.repeat <= 0.0 {
+            focus.repeat = MENU_REPEAT_INTERVAL;
+            step = Some(direction);
+        }
+    }
+    focus.direction = direction;
+    let confirm = gamepads
+        .iter()
+        .any(|pad| pad.just_pressed(GamepadButton::South));
+    let back = gamepads
+        .iter()
+        .any(|pad| pad.just_pressed(GamepadButton::East));
+
+    let candidates: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(_, _, visibility, _)| visibility.get())
+        .map(|(entity, transform, _, _)| (entity, transform.translation().truncate()))
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    if let Some(step) = step.filter(|step| *step != Vec2::ZERO) {
+        focus.focused = match focus.focused {
+            Some(current) => Some(neighbour(&candidates, current, step).unwrap_or(current)),
+            None => first(&candidates),
+        };
+    } else if confirm && focus.focused.is_none() {
+        focus.focused = first(&candidates);
+        return;
+    }
+
+    if back {
+        for (entity, _) in &candidates {
+            if back_buttons.contains(*entity)
+                && let Ok((_, _, _, mut interaction)) = buttons.get_mut(*entity)
+            {
+                *interaction = Interaction::Pressed;
+                focus.pressed = Some(*entity);
+            }
+        }
+    }
+
+    let Some(focused) = focus.focused else {
+        return;
+    };
+    if let Ok((_, _, _, mut interaction)) = buttons.get_mut(focused) {
+        if confirm {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(focused);
+        } else {
+            interaction.set_if_neq(Interaction::Hovered);
+        }
+    }
+}
+
+// UI positions grow downwards, while stick and d-pad up is positive.
+fn snap_to_axis(direction: Vec2) -> Vec2 {
+    if direction.x.abs() > direction.y.abs() {
+        Vec2::new(direction.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, -direction.y.signum())
+    }
+}
+
+fn first(candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+    candidates
+        .iter()
+        .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+        .map(|(entity, _)| *entity)
+}
+
+// The closest button in the given direction, preferring ones that are
+// lined up with the current button over ones off to the side.
+fn neighbour(candidates: &[(Entity, Vec2)], current: Entity, direction: Vec2) -> Option<Entity> {
+    let (_, from) = candidates.iter().find(|(entity, _)| *entity == current)?;
+    candidates
+        .iter()
+        .filter_map(|(entity, position)| {
+            let offset = *position - *from;
+            let along = offset.dot(direction);
+            let across = (offset - direction * along).length();
+            (along > 1.0).then_some((*entity, along + across * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::{game_state::GameState, player::player::Player};
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+// Time after a hit during which further hits are ignored.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Invulnerability {
+    pub duration: f32,
+    pub remaining: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Pierce,
+    Crush,
+    Acid,
+    Fall,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+    pub kind: DamageType,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    pub kind: DamageType,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+impl Invulnerability {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            remaining: 0.0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+}
+
+impl DamageType {
+    // Damage dealt every frame, like standing in acid, or that the player
+    // can't dodge, like falling out of the map, ignores invulnerability and
+    // doesn't grant it.
+    pub fn bypasses_invulnerability(&self) -> bool {
+        matches!(self, DamageType::Acid | DamageType::Fall)
+    }
+}
+
+pub fn tick_invulnerability(time: Res<Time>, mut targets: Query<&mut Invulnerability>) {
+    for mut invulnerability in &mut targets {
+        if invulnerability.is_active() {
+            invulnerability.remaining -= time.delta_secs();
+        }
+    }
+}
+
+pub fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut targets: Query<(&mut Health, Option<&mut Invulnerability>)>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    for event in events.read() {
+        let Ok((mut health, mut invulnerability)) = targets.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+        if let Some(invulnerability) = invulnerability
+            .as_mut()
+            .filter(|_| !event.kind.bypasses_invulnerability())
+        {
+            if invulnerability.is_active() {
+                continue;
+            }
+            invulnerability.remaining = invulnerability.duration;
+        }
+
+        health.current = (health.current - event.amount).clamp(0.0, health.max);
+        if health.is_dead() {
+            deaths.write(DeathEvent {
+                entity: event.target,
+                source: event.source,
+                kind: event.kind,
+            });
+        }
+    }
+}
+
+pub fn handle_player_death(
+    mut deaths: EventReader<DeathEvent>,
+    player: Query<(), With<Player>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for death in deaths.read() {
+        if player.contains(death.entity) {
+            info!("Player died from {:?} damage", death.kind);
+            game_state.set(GameState::GameOver);
+        }
+    }
+}
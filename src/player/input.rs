@@ -1,49 +1,80 @@
-use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy::prelude::*;
 use bevy_rapier3d::{control::KinematicCharacterController, prelude::*};
 
 use crate::{
-    constants::player::{GRAVITY, GROUND_TIMER, JUMP_SPEED, MOUSE_SENSITIVITY, MOVEMENT_SPEED},
-    entities::map::map::RoomBoundsData,
-    player::weapons::{Sword, SwordSlash},
-    ui::{
-        EscButtonState, PauseOverlay, PauseState, despawn_pause_ui,
-        hud::{RoomNameDisplay, update_room_display_text},
-        spawn_pause_ui,
+    constants::player::{
+        DODGE_DURATION, DODGE_SPEED, DODGE_STAMINA, EXHAUSTED_SPEED_MULTIPLIER, GRAVITY,
+        GROUND_TIMER, JUMP_SPEED, JUMP_STAMINA, MOVEMENT_SPEED, SPRINT_STAMINA_PER_SECOND,
     },
+    entities::map::platform::PlatformRider,
+    input::{Action, ActionState},
+    player::{health::Invulnerability, stamina::Stamina},
+    ui::{EscButtonState, PauseOverlay, PauseState, despawn_pause_ui, spawn_pause_ui},
 };
 
-#[derive(Default, Resource, Deref, DerefMut)]
-pub struct MovementInput(Vec3);
+// The held direction on the ground plane is rewritten every frame. A jump is
+// kept until a physics tick uses it, so short presses are not lost between
+// ticks.
+#[derive(Default, Resource)]
+pub struct MovementInput {
+    pub direction: Vec3,
+    pub jump: bool,
+}
 
 #[derive(Default, Resource, Deref, DerefMut)]
 pub struct LookInput(Vec2);
 
+// Requests that cost stamina, kept until the system that handles them runs.
+// The dodge direction is relative to the player.
+#[derive(Default, Resource)]
+pub struct CombatInput {
+    pub attack: bool,
+    pub dodge: Option<Vec3>,
+}
+
+#[derive(Component, Default, Debug)]
+pub struct Dodge {
+    velocity: Vec3,
+    remaining: f32,
+}
+
+#[derive(Component, Default, Debug)]
+pub struct Motion {
+    vertical_velocity: f32,
+    grounded_timer: f32,
+}
+
+impl Motion {
+    // Drops any fall speed, e.g. after the player is teleported.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 pub fn handle_input(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut movement: ResMut<MovementInput>,
     mut look: ResMut<LookInput>,
-    mut mouse_events: EventReader<MouseMotion>,
-    mouse: Res<ButtonInput<MouseButton>>,
     mut pause_state: ResMut<PauseState>,
     mut commands: Commands,
     pause_query: Query<Entity, With<PauseOverlay>>,
     mut esc_state: ResMut<EscButtonState>,
     time: Res<Time>,
     mut hold_timer: Local<f32>,
-    sword_query: Query<Entity, With<Sword>>,
+    mut combat: ResMut<CombatInput>,
 ) {
     if pause_state.is_paused {
-        if keyboard.just_pressed(KeyCode::Escape) {
+        if actions.just_pressed(Action::Pause) {
             *hold_timer = 0.0;
             esc_state.progress = 0;
         }
 
-        if keyboard.pressed(KeyCode::Escape) {
+        if actions.pressed(Action::Pause) {
             *hold_timer += time.delta_secs();
             esc_state.progress = (*hold_timer * 5.0).clamp(0.0, 6.0) as u8;
         }
 
-        if keyboard.just_released(KeyCode::Escape) {
+        if actions.just_released(Action::Pause) {
             if *hold_timer < 0.5 {
                 pause_state.is_paused = false;
                 despawn_pause_ui(&mut commands, pause_query);
@@ -56,7 +87,7 @@ pub fn handle_input(
         return;
     }
 
-    if keyboard.just_released(KeyCode::Escape) {
+    if actions.just_released(Action::Pause) {
         pause_state.is_paused = true;
         spawn_pause_ui(&mut commands);
         *hold_timer = 0.0;
@@ -65,41 +96,29 @@ pub fn handle_input(
     }
 
     if !pause_state.is_paused {
-        if mouse.just_pressed(MouseButton::Left) {
-            info!("Sword is swinging");
-            for sword_entity in sword_query.iter() {
-                commands.entity(sword_entity).insert(SwordSlash {
-                    start_time: time.elapsed_secs(),
-                    duration: 0.8,
-                });
-            }
+        if actions.just_pressed(Action::Attack) {
+            combat.attack = true;
         }
 
-        if keyboard.pressed(KeyCode::KeyW) {
-            movement.z -= 1.0;
-        }
-        if keyboard.pressed(KeyCode::KeyS) {
-            movement.z += 1.0
+        let direction = actions.movement();
+        let mut direction = Vec3::new(direction.x, 0.0, -direction.y);
+        if actions.just_pressed(Action::Dodge) {
+            combat.dodge = Some(if direction == Vec3::ZERO {
+                Vec3::Z
+            } else {
+                direction
+            });
         }
-        if keyboard.pressed(KeyCode::KeyA) {
-            movement.x -= 1.0;
+        if actions.pressed(Action::Sprint) {
+            direction = direction.normalize_or_zero() * 2.0;
         }
-        if keyboard.pressed(KeyCode::KeyD) {
-            movement.x += 1.0
-        }
-        **movement = movement.normalize_or_zero();
-        if keyboard.pressed(KeyCode::ShiftLeft) {
-            **movement *= 2.0;
-        }
-        if keyboard.pressed(KeyCode::Space) {
-            movement.y = 1.0;
+        movement.direction = direction;
+        if actions.pressed(Action::Jump) {
+            movement.jump = true;
         }
 
-        for event in mouse_events.read() {
-            look.x -= event.delta.x * MOUSE_SENSITIVITY;
-            look.y -= event.delta.y * MOUSE_SENSITIVITY;
-            look.y = look.y.clamp(-89.9, 89.9);
-        }
+        **look -= actions.look();
+        look.y = look.y.clamp(-89.9, 89.9);
     }
 }
 
@@ -107,69 +126,101 @@ pub fn player_movement(
     time: Res<Time>,
     mut input: ResMut<MovementInput>,
     mut player: Query<(
-        &mut Transform,
+        &Transform,
         &mut KinematicCharacterController,
         Option<&KinematicCharacterControllerOutput>,
+        Option<&mut PlatformRider>,
     )>,
-    mut vertical_movement: Local<f32>,
-    mut grounded_timer: Local<f32>,
-    bounds_data: Res<RoomBoundsData>,
-    room_name_q: Query<&mut Text, With<RoomNameDisplay>>,
+    mut motion: Query<&mut Motion>,
+    mut stamina: Query<&mut Stamina>,
 ) {
-    let Ok((transform, mut controller, output)) = player.single_mut() else {
+    let Ok((transform, mut controller, output, mut rider)) = player.single_mut() else {
         return;
     };
+    let Ok(mut motion) = motion.single_mut() else {
+        return;
+    };
+    let mut stamina = stamina.single_mut().ok();
     let delta_time = time.delta_secs();
-    let mut movement = Vec3::new(input.x, 0.0, input.z) * MOVEMENT_SPEED;
-    let jump_speed = input.y * JUMP_SPEED;
-    **input = Vec3::ZERO;
+    let mut movement = input.direction;
+    if let Some(stamina) = stamina.as_mut() {
+        let sprinting = movement.length() > 1.0;
+        if sprinting && !stamina.try_spend(SPRINT_STAMINA_PER_SECOND * delta_time) {
+            movement = movement.normalize_or_zero();
+        }
+        if stamina.exhausted {
+            movement *= EXHAUSTED_SPEED_MULTIPLIER;
+        }
+    }
+    movement *= MOVEMENT_SPEED;
+    let jump = std::mem::take(&mut input.jump);
+    let mut carried = rider.as_ref().and_then(|rider| rider.velocity);
     if output.map(|o| o.grounded).unwrap_or(false) {
-        *grounded_timer = GROUND_TIMER;
-        *vertical_movement = 0.0;
+        motion.grounded_timer = GROUND_TIMER;
+        motion.vertical_velocity = 0.0;
+        if let Some(rider) = rider.as_mut() {
+            rider.momentum = Vec3::ZERO;
+        }
     }
-    if *grounded_timer > 0.0 {
-        *grounded_timer -= delta_time;
-        if jump_speed > 0.0 {
-            *vertical_movement = jump_speed;
-            *grounded_timer = 0.0;
+    if motion.grounded_timer > 0.0 {
+        motion.grounded_timer -= delta_time;
+        if jump
+            && stamina
+                .as_mut()
+                .is_none_or(|stamina| stamina.try_spend(JUMP_STAMINA))
+        {
+            motion.vertical_velocity = JUMP_SPEED;
+            motion.grounded_timer = 0.0;
+            if let (Some(rider), Some(velocity)) = (rider.as_mut(), carried.take()) {
+                motion.vertical_velocity += velocity.y.max(0.0);
+                rider.momentum = velocity.with_y(0.0);
+            }
         }
     }
-    movement.y = *vertical_movement;
-    *vertical_movement += GRAVITY * delta_time * controller.custom_mass.unwrap_or(1.0);
-    controller.translation = Some(transform.rotation * (movement * delta_time));
-
-    calculate_player_room_location(&mut player, bounds_data, room_name_q);
+    movement.y = motion.vertical_velocity;
+    motion.vertical_velocity += GRAVITY * delta_time * controller.custom_mass.unwrap_or(1.0);
+    let carried = carried
+        .or(rider.map(|rider| rider.momentum))
+        .unwrap_or_default();
+    controller.translation =
+        Some(transform.rotation * (movement * delta_time) + carried * delta_time);
 }
 
-fn calculate_player_room_location(
-    player: &mut Query<(
-        &mut Transform,
+pub fn dodge_roll(
+    time: Res<Time>,
+    mut combat: ResMut<CombatInput>,
+    mut player: Query<(
+        &Transform,
         &mut KinematicCharacterController,
-        Option<&KinematicCharacterControllerOutput>,
+        &mut Dodge,
+        &mut Stamina,
+        Option<&mut Invulnerability>,
     )>,
-    bounds_data: Res<RoomBoundsData>,
-    room_name_q: Query<&mut Text, With<RoomNameDisplay>>,
 ) {
-    let Ok((player_transform, _, _)) = player.single_mut() else {
+    let Ok((transform, mut controller, mut dodge, mut stamina, invulnerability)) =
+        player.single_mut()
+    else {
         return;
     };
 
-    let player_pos = player_transform.translation;
-
-    for bound in &bounds_data.bounds {
-        if player_pos.x >= bound.min.x
-            && player_pos.x <= bound.max.x
-            && player_pos.y >= bound.min.y
-            && player_pos.y <= bound.max.y
-            && player_pos.z >= bound.min.z
-            && player_pos.z <= bound.max.z
-        {
-            update_room_display_text(Some(&bound.name), room_name_q);
-            return;
+    if let Some(direction) = combat.dodge.take()
+        && dodge.remaining <= 0.0
+        && stamina.try_spend(DODGE_STAMINA)
+    {
+        dodge.velocity = transform.rotation * direction.normalize_or_zero() * DODGE_SPEED;
+        dodge.remaining = DODGE_DURATION;
+        if let Some(mut invulnerability) = invulnerability {
+            invulnerability.remaining = invulnerability.remaining.max(DODGE_DURATION);
         }
     }
+    if dodge.remaining <= 0.0 {
+        return;
+    }
 
-    update_room_display_text(None, room_name_q);
+    let delta_time = time.delta_secs();
+    dodge.remaining -= delta_time;
+    controller.translation =
+        Some(controller.translation.unwrap_or_default() + dodge.velocity * delta_time);
 }
 
 pub fn player_look(
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::constants::{physics::MAX_INTERPOLATION_DISTANCE, player::CAMERA_OFFSET};
+
+// The player's position after the last two physics ticks. The body itself
+// stays where physics put it; only the camera is drawn in between.
+#[derive(Component, Debug)]
+pub struct PhysicsInterpolation {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl PhysicsInterpolation {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            previous: position,
+            current: position,
+        }
+    }
+
+    pub fn record(&mut self, position: Vec3) {
+        self.previous = if position.distance(self.current) > MAX_INTERPOLATION_DISTANCE {
+            position
+        } else {
+            self.current
+        };
+        self.current = position;
+    }
+
+    pub fn offset(&self, fraction: f32) -> Vec3 {
+        self.previous.lerp(self.current, fraction) - self.current
+    }
+}
+
+pub fn record_physics_translation(mut bodies: Query<(&Transform, &mut PhysicsInterpolation)>) {
+    for (transform, mut interpolation) in &mut bodies {
+        interpolation.record(transform.translation);
+    }
+}
+
+pub fn interpolate_camera(
+    fixed_time: Res<Time<Fixed>>,
+    player: Query<(&Transform, &PhysicsInterpolation), Without<Camera>>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+) {
+    let Ok((transform, interpolation)) = player.single() else {
+        return;
+    };
+    let Ok(mut camera) = camera.single_mut() else {
+        return;
+    };
+    let offset = interpolation.offset(fixed_time.overstep_fraction());
+    camera.translation = CAMERA_OFFSET + transform.rotation.inverse() * offset;
+}
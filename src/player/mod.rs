@@ -1,6 +1,11 @@
+pub mod health;
 pub mod input;
+pub mod interpolation;
 pub mod player;
+pub mod stamina;
 pub mod weapons;
 
-pub use input::{LookInput, MovementInput, handle_input, player_look, player_movement};
+pub use input::{
+    CombatInput, LookInput, MovementInput, dodge_roll, handle_input, player_look, player_movement,
+};
 pub use player::{despawn_player, setup_player};
@@ -1,6 +1,20 @@
 use bevy::prelude::*;
 use bevy_rapier3d::{control::KinematicCharacterController, prelude::*};
 
+use crate::{
+    constants::player::{
+        CAMERA_OFFSET, PLAYER_INVULNERABILITY_TIME, PLAYER_MAX_HEALTH, PLAYER_MAX_STAMINA,
+        PLAYER_SPAWN_POSITION,
+    },
+    entities::map::{platform::PlatformRider, trigger::TriggerActor},
+    player::{
+        health::{Health, Invulnerability},
+        input::{Dodge, Motion},
+        interpolation::PhysicsInterpolation,
+        stamina::Stamina,
+    },
+};
+
 #[derive(Component)]
 pub struct Player;
 
@@ -9,7 +23,15 @@ pub fn setup_player(commands: &mut Commands) -> Entity {
     commands
         .spawn((
             Player,
-            Transform::from_xyz(0.0, 5.0, 0.0),
+            TriggerActor::Player,
+            Health::new(PLAYER_MAX_HEALTH),
+            Invulnerability::new(PLAYER_INVULNERABILITY_TIME),
+            Stamina::new(PLAYER_MAX_STAMINA),
+            Dodge::default(),
+            Motion::default(),
+            PhysicsInterpolation::new(PLAYER_SPAWN_POSITION),
+            PlatformRider::default(),
+            Transform::from_translation(PLAYER_SPAWN_POSITION),
             Visibility::default(),
             Collider::round_cylinder(0.9, 0.3, 0.2),
             KinematicCharacterController {
@@ -31,8 +53,11 @@ pub fn setup_player(commands: &mut Commands) -> Entity {
         ))
         .with_children(|b| {
             camera_entity = Some(
-                b.spawn((Camera3d::default(), Transform::from_xyz(0.0, 0.2, -0.1)))
-                    .id(),
+                b.spawn((
+                    Camera3d::default(),
+                    Transform::from_translation(CAMERA_OFFSET),
+                ))
+                .id(),
             );
         });
     camera_entity.unwrap()
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::constants::player::{
+    EXHAUSTED_REGEN_DELAY, STAMINA_RECOVERY_FRACTION, STAMINA_REGEN_DELAY, STAMINA_REGEN_RATE,
+};
+
+// An action can start as long as any stamina is left, even if it costs more.
+// Running out leaves the player exhausted until enough has come back.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    pub exhausted: bool,
+    regen_delay: f32,
+}
+
+impl Stamina {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            exhausted: false,
+            regen_delay: 0.0,
+        }
+    }
+
+    pub fn can_spend(&self) -> bool {
+        !self.exhausted && self.current > 0.0
+    }
+
+    pub fn try_spend(&mut self, amount: f32) -> bool {
+        if !self.can_spend() {
+            return false;
+        }
+
+        self.current -= amount;
+        self.regen_delay = STAMINA_REGEN_DELAY;
+        if self.current <= 0.0 {
+            self.current = 0.0;
+            self.exhausted = true;
+            self.regen_delay = EXHAUSTED_REGEN_DELAY;
+        }
+        true
+    }
+}
+
+pub fn regenerate_stamina(time: Res<Time>, mut targets: Query<&mut Stamina>) {
+    let delta = time.delta_secs();
+    for mut stamina in &mut targets {
+        if stamina.regen_delay > 0.0 {
+            stamina.regen_delay -= delta;
+            continue;
+        }
+        if stamina.current >= stamina.max {
+            continue;
+        }
+
+        stamina.current = (stamina.current + STAMINA_REGEN_RATE * delta).min(stamina.max);
+        if stamina.exhausted && stamina.current >= stamina.max * STAMINA_RECOVERY_FRACTION {
+            stamina.exhausted = false;
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::player::player::Player;
+use crate::{
+    constants::player::{SLASH_STAMINA, SWORD_ARC_COS, SWORD_REACH},
+    player::{input::CombatInput, player::Player, stamina::Stamina},
+};
 
 #[derive(Component)]
 pub struct Sword;
@@ -11,6 +14,20 @@ pub struct SwordSlash {
     pub duration: f32,
 }
 
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SwordStrike {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl SwordStrike {
+    pub fn hits(&self, point: Vec3) -> bool {
+        let offset = point - self.origin;
+        offset.length() <= SWORD_REACH
+            && offset.normalize_or_zero().dot(self.direction) >= SWORD_ARC_COS
+    }
+}
+
 #[derive(Component, Clone, Copy)]
 pub struct SwordRestTransform {
     pub translation: Vec3,
@@ -87,6 +104,46 @@ pub fn animate_sword_slash(
     }
 }
 
+pub fn start_sword_slash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut combat: ResMut<CombatInput>,
+    mut player: Query<&mut Stamina, With<Player>>,
+    swords: Query<Entity, With<Sword>>,
+) {
+    if !std::mem::take(&mut combat.attack) {
+        return;
+    }
+    if let Ok(mut stamina) = player.single_mut()
+        && !stamina.try_spend(SLASH_STAMINA)
+    {
+        return;
+    }
+
+    info!("Sword is swinging");
+    for sword in &swords {
+        commands.entity(sword).insert(SwordSlash {
+            start_time: time.elapsed_secs(),
+            duration: 0.8,
+        });
+    }
+}
+
+pub fn emit_sword_strikes(
+    swords: Query<&ChildOf, (With<Sword>, Changed<SwordSlash>)>,
+    cameras: Query<&GlobalTransform>,
+    mut strikes: EventWriter<SwordStrike>,
+) {
+    for parent in &swords {
+        if let Ok(camera) = cameras.get(parent.parent()) {
+            strikes.write(SwordStrike {
+                origin: camera.translation(),
+                direction: camera.forward().into(),
+            });
+        }
+    }
+}
+
 pub fn spawn_weapons(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
@@ -11,7 +11,16 @@ use bevy::{
     utils::default,
 };
 
-use crate::constants::hud::DEFAULT_ROOM_NAME;
+use crate::{
+    constants::hud::{DEFAULT_ROOM_NAME, EXHAUSTED_COLOR, SECRET_MESSAGE_TIME, STAMINA_COLOR},
+    entities::map::{
+        room_tracking::{RoomEntered, RoomExited},
+        secret::{SecretDiscovered, SecretStats},
+        trigger::{TriggerActor, TriggerEntered, TriggerExited, TriggerKind},
+    },
+    input::InputBindings,
+    player::{health::Health, player::Player, stamina::Stamina},
+};
 
 #[derive(Component)]
 pub struct HUD;
@@ -19,6 +28,20 @@ pub struct HUD;
 #[derive(Component)]
 pub struct RoomNameDisplay;
 
+#[derive(Component)]
+pub struct PromptDisplay;
+
+#[derive(Component)]
+pub struct HealthDisplay;
+
+#[derive(Component)]
+pub struct StaminaDisplay;
+
+#[derive(Component, Default)]
+pub struct SecretDisplay {
+    remaining: f32,
+}
+
 fn setup_room_display(commands: &mut Commands, windows: &Query<&mut Window>) {
     let window = windows.single().unwrap();
     let font_size = window.height() * 0.03;
@@ -44,6 +67,100 @@ fn setup_room_display(commands: &mut Commands, windows: &Query<&mut Window>) {
     ));
 }
 
+fn setup_prompt_display(commands: &mut Commands, windows: &Query<&mut Window>) {
+    let window = windows.single().unwrap();
+    let font_size = window.height() * 0.025;
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextShadow::default(),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextColor(Color::srgba(0.9, 0.85, 0.7, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(120.0),
+            left: Val::Percent(40.0),
+            ..default()
+        },
+        HUD,
+        PromptDisplay,
+    ));
+}
+
+fn setup_health_display(commands: &mut Commands, windows: &Query<&mut Window>) {
+    let window = windows.single().unwrap();
+    let font_size = window.height() * 0.025;
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextShadow::default(),
+        TextColor(Color::srgba(0.85, 0.3, 0.3, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(24.0),
+            left: Val::Px(24.0),
+            ..default()
+        },
+        HUD,
+        HealthDisplay,
+    ));
+}
+
+fn setup_stamina_display(commands: &mut Commands, windows: &Query<&mut Window>) {
+    let window = windows.single().unwrap();
+    let font_size = window.height() * 0.025;
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextShadow::default(),
+        TextColor(STAMINA_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(24.0 + font_size * 1.5),
+            left: Val::Px(24.0),
+            ..default()
+        },
+        HUD,
+        StaminaDisplay,
+    ));
+}
+
+fn setup_secret_display(commands: &mut Commands, windows: &Query<&mut Window>) {
+    let window = windows.single().unwrap();
+    let font_size = window.height() * 0.025;
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextShadow::default(),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextColor(Color::srgba(0.6, 0.8, 1.0, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(130.0),
+            left: Val::Percent(42.0),
+            ..default()
+        },
+        HUD,
+        SecretDisplay::default(),
+    ));
+}
+
 pub fn update_room_display_text(
     new_text: Option<&str>,
     mut room_name_q: Query<&mut Text, With<RoomNameDisplay>>,
@@ -56,8 +173,107 @@ pub fn update_room_display_text(
     }
 }
 
+pub fn update_room_name_display(
+    mut entered: EventReader<RoomEntered>,
+    mut exited: EventReader<RoomExited>,
+    room_name_q: Query<&mut Text, With<RoomNameDisplay>>,
+) {
+    let mut changed = false;
+    let mut name = None;
+
+    for event in exited.read() {
+        debug!("Left room {:?} ({})", event.room, event.name);
+        changed = true;
+    }
+    for event in entered.read() {
+        debug!("Entered room {:?} ({})", event.room, event.name);
+        name = Some(event.name.clone());
+        changed = true;
+    }
+
+    if changed {
+        update_room_display_text(name.as_deref(), room_name_q);
+    }
+}
+
+pub fn update_prompt_display(
+    mut entered: EventReader<TriggerEntered>,
+    mut exited: EventReader<TriggerExited>,
+    mut prompt_q: Query<&mut Text, With<PromptDisplay>>,
+    bindings: Res<InputBindings>,
+) {
+    let Ok(mut text) = prompt_q.single_mut() else {
+        return;
+    };
+
+    for event in exited.read() {
+        if event.actor_kind == TriggerActor::Player && matches!(event.kind, TriggerKind::Prompt(_))
+        {
+            text.0.clear();
+        }
+    }
+    for event in entered.read() {
+        if event.actor_kind != TriggerActor::Player {
+            continue;
+        }
+        if let TriggerKind::Prompt(prompt) = &event.kind {
+            text.0 = bindings.fill_prompt(prompt);
+        }
+    }
+}
+
+pub fn update_health_display(
+    player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut health_q: Query<&mut Text, With<HealthDisplay>>,
+) {
+    let (Ok(health), Ok(mut text)) = (player.single(), health_q.single_mut()) else {
+        return;
+    };
+    text.0 = format!("Health {:.0}/{:.0}", health.current.ceil(), health.max);
+}
+
+pub fn update_stamina_display(
+    player: Query<&Stamina, Changed<Stamina>>,
+    mut stamina_q: Query<(&mut Text, &mut TextColor), With<StaminaDisplay>>,
+) {
+    let (Ok(stamina), Ok((mut text, mut color))) = (player.single(), stamina_q.single_mut()) else {
+        return;
+    };
+    text.0 = format!("Stamina {:.0}/{:.0}", stamina.current.floor(), stamina.max);
+    color.0 = if stamina.exhausted {
+        EXHAUSTED_COLOR
+    } else {
+        STAMINA_COLOR
+    };
+}
+
+pub fn update_secret_display(
+    time: Res<Time>,
+    mut discovered: EventReader<SecretDiscovered>,
+    stats: Res<SecretStats>,
+    mut secret_q: Query<(&mut Text, &mut SecretDisplay)>,
+) {
+    let Ok((mut text, mut display)) = secret_q.single_mut() else {
+        return;
+    };
+
+    if discovered.read().last().is_some() {
+        text.0 = format!("Secret found! ({}/{})", stats.found, stats.total);
+        display.remaining = SECRET_MESSAGE_TIME;
+    } else if display.remaining > 0.0 {
+        display.remaining -= time.delta_secs();
+        if display.remaining <= 0.0 {
+            text.0.clear();
+        }
+    }
+}
+
 pub fn setup_hud(commands: &mut Commands, windows: &Query<&mut Window>) {
     setup_room_display(commands, windows);
+    setup_prompt_display(commands, windows);
+    setup_health_display(commands, windows);
+    setup_stamina_display(commands, windows);
+    setup_secret_display(commands, windows);
 }
 
 pub fn cleanup_hud(mut commands: Commands, hud_query: Query<Entity, With<HUD>>) {
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use bevy::{
+    asset::AssetPlugin,
+    input::{InputPlugin, InputSystem},
+    prelude::*,
+    render::mesh::MeshPlugin,
+    scene::ScenePlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_rapier3d::prelude::*;
+use dark_city::{
+    constants::physics::PHYSICS_TICK_RATE,
+    input::{ActionState, InputBindings, update_action_axes, update_action_state},
+    player::{
+        input::{CombatInput, LookInput, MovementInput, dodge_roll, handle_input, player_movement},
+        interpolation::record_physics_translation,
+        player::setup_player,
+    },
+    ui::{EscButtonState, PauseState},
+};
+
+const SETTLE_TIME: f32 = 2.0;
+const RUN_TIME: f32 = 4.0;
+const TOLERANCE: f32 = 0.05;
+const FRAME_RATES: [f64; 3] = [30.0, 60.0, 240.0];
+
+// One entry per physics tick: where the player ended up and whether it was
+// standing on something.
+#[derive(Resource, Default)]
+struct Ticks(Vec<(Vec3, bool)>);
+
+fn record_tick(
+    mut ticks: ResMut<Ticks>,
+    player: Query<
+        (&Transform, Option<&KinematicCharacterControllerOutput>),
+        With<KinematicCharacterController>,
+    >,
+) {
+    if let Ok((transform, output)) = player.single() {
+        let grounded = output.is_some_and(|output| output.grounded);
+        ticks.0.push((transform.translation, grounded));
+    }
+}
+
+fn player_app(fps: f64) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        InputPlugin,
+        TransformPlugin,
+        AssetPlugin::default(),
+        MeshPlugin,
+        ScenePlugin,
+        RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule(),
+    ))
+    .insert_resource(Time::<Fixed>::from_hz(PHYSICS_TICK_RATE))
+    .insert_resource(TimestepMode::Fixed {
+        dt: 1.0 / PHYSICS_TICK_RATE as f32,
+        substeps: 1,
+    })
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / fps,
+    )))
+    .init_resource::<InputBindings>()
+    .init_resource::<ActionState>()
+    .init_resource::<MovementInput>()
+    .init_resource::<LookInput>()
+    .init_resource::<CombatInput>()
+    .init_resource::<PauseState>()
+    .init_resource::<EscButtonState>()
+    .init_resource::<Ticks>()
+    .add_systems(
+        PreUpdate,
+        (update_action_state, update_action_axes, handle_input)
+            .chain()
+            .after(InputSystem),
+    )
+    .add_systems(
+        FixedUpdate,
+        (player_movement, dodge_roll)
+            .chain()
+            .before(PhysicsSet::SyncBackend),
+    )
+    .add_systems(
+        FixedUpdate,
+        (record_physics_translation, record_tick)
+            .chain()
+            .after(PhysicsSet::Writeback),
+    );
+    app.finish();
+    app.cleanup();
+
+    app.world_mut().spawn((
+        Transform::from_xyz(0.0, -0.5, 0.0),
+        Collider::cuboid(100.0, 0.5, 100.0),
+    ));
+    setup_player(&mut app.world_mut().commands());
+    app.world_mut().flush();
+    app
+}
+
+// Settles the player on the ground, then holds `held` and taps `tapped` for a
+// single frame, returning the ticks recorded after settling.
+fn simulate(fps: f64, held: &[KeyCode], tapped: &[KeyCode]) -> Vec<(Vec3, bool)> {
+    let mut app = player_app(fps);
+    let frames = |seconds: f32| (seconds as f64 * fps).round() as usize;
+    for _ in 0..frames(SETTLE_TIME) {
+        app.update();
+    }
+    app.world_mut().resource_mut::<Ticks>().0.clear();
+
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    for key in held.iter().chain(tapped) {
+        keys.press(*key);
+    }
+    app.update();
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    for key in tapped {
+        keys.release(*key);
+    }
+    for _ in 1..frames(RUN_TIME) {
+        app.update();
+    }
+    std::mem::take(&mut app.world_mut().resource_mut::<Ticks>().0)
+}
+
+// Taps jump while running forward and measures the height of the jump and
+// how far the player got before landing. At high frame rates the tap ends
+// before a physics tick has used it.
+fn running_jump(fps: f64, held: &[KeyCode]) -> (f32, f32) {
+    let ticks = simulate(fps, held, &[KeyCode::Space]);
+
+    let (start, _) = ticks[0];
+    let takeoff = ticks.iter().position(|(_, grounded)| !grounded).unwrap();
+    let landing = takeoff
+        + ticks[takeoff..]
+            .iter()
+            .position(|(_, grounded)| *grounded)
+            .unwrap();
+    let apex = ticks[..landing]
+        .iter()
+        .map(|(position, _)| position.y - start.y)
+        .fold(0.0, f32::max);
+    let travel = ticks[landing].0.xz().distance(start.xz());
+    (apex, travel)
+}
+
+fn dodge_distance(fps: f64) -> f32 {
+    let ticks = simulate(fps, &[], &[KeyCode::ControlLeft]);
+    let (start, _) = ticks[0];
+    let (end, _) = ticks[ticks.len() - 1];
+    end.xz().distance(start.xz())
+}
+
+#[test]
+fn running_jump_is_the_same_at_any_frame_rate() {
+    let (apex, travel) = running_jump(PHYSICS_TICK_RATE, &[KeyCode::KeyW]);
+    assert!(apex > 1.0 && travel > 1.0, "apex {apex}, travel {travel}");
+
+    for fps in FRAME_RATES {
+        let (other_apex, other_travel) = running_jump(fps, &[KeyCode::KeyW]);
+        assert!(
+            (other_apex - apex).abs() < TOLERANCE,
+            "apex {other_apex} at {fps} FPS, {apex} at the tick rate"
+        );
+        assert!(
+            (other_travel - travel).abs() < TOLERANCE,
+            "travel {other_travel} at {fps} FPS, {travel} at the tick rate"
+        );
+    }
+}
+
+#[test]
+fn dodge_covers_the_same_distance_at_any_frame_rate() {
+    let distance = dodge_distance(PHYSICS_TICK_RATE);
+    assert!(distance > 1.0, "distance {distance}");
+
+    for fps in FRAME_RATES {
+        let other = dodge_distance(fps);
+        assert!(
+            (other - distance).abs() < TOLERANCE,
+            "dodge {other} at {fps} FPS, {distance} at the tick rate"
+        );
+    }
+}
+
+#[test]
+fn sprinting_only_speeds_up_the_run() {
+    let (apex, travel) = running_jump(PHYSICS_TICK_RATE, &[KeyCode::KeyW]);
+
+    for fps in FRAME_RATES {
+        let (sprint_apex, sprint_travel) = running_jump(fps, &[KeyCode::KeyW, KeyCode::ShiftLeft]);
+        assert!(
+            (sprint_apex - apex).abs() < TOLERANCE,
+            "sprint apex {sprint_apex} at {fps} FPS, {apex} running"
+        );
+        assert!(
+            (sprint_travel - travel * 2.0).abs() < TOLERANCE,
+            "sprint travel {sprint_travel} at {fps} FPS, {travel} running"
+        );
+    }
+}
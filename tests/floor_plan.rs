@@ -0,0 +1,13 @@
+use dark_city::entities::map::{floor_plan::render_ascii, level::parse_level};
+
+// Regenerate with
+// `cargo run --bin floor_plan assets/levels/default.level.ron -o tests/snapshots/default_level.txt`
+#[test]
+fn default_level_ascii_plan_matches_snapshot() {
+    let path = "assets/levels/default.level.ron";
+    let bytes = std::fs::read(path).unwrap();
+    let level = parse_level(path, &bytes).unwrap();
+
+    let expected = include_str!("snapshots/default_level.txt");
+    assert_eq!(render_ascii(&level.rooms), expected);
+}
@@ -0,0 +1,11 @@
+use dark_city::entities::map::{level::parse_level, validation::validate_layout};
+
+#[test]
+fn default_level_layout_is_valid() {
+    let path = "assets/levels/default.level.ron";
+    let bytes = std::fs::read(path).unwrap();
+    let level = parse_level(path, &bytes).unwrap();
+
+    let issues = validate_layout(&level.rooms, &level.mechanisms);
+    assert!(issues.is_empty(), "{issues:#?}");
+}
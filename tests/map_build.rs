@@ -0,0 +1,110 @@
+use bevy::{
+    asset::AssetPlugin, ecs::system::RunSystemOnce, prelude::*, render::mesh::MeshPlugin,
+    scene::ScenePlugin,
+};
+use dark_city::entities::map::{
+    builder::{MapBuildStats, MapBuilder},
+    level::{Level, LevelHandle, parse_level},
+    map::{MapEntity, setup_map},
+    room_tracking::RoomId,
+    streaming::LevelStreaming,
+};
+
+fn build_default_level() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        AssetPlugin::default(),
+        MeshPlugin,
+        ScenePlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Level>();
+
+    let path = "assets/levels/default.level.ron";
+    let level = parse_level(path, &std::fs::read(path).unwrap()).unwrap();
+    let handle = LevelHandle(
+        app.world_mut()
+            .resource_mut::<Assets<Level>>()
+            .add(level.clone()),
+    );
+    app.add_systems(
+        Startup,
+        move |mut commands: Commands,
+              mut meshes: ResMut<Assets<Mesh>>,
+              mut materials: ResMut<Assets<StandardMaterial>>,
+              asset_server: Res<AssetServer>| {
+            setup_map(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &asset_server,
+                &handle,
+                &level,
+            );
+        },
+    );
+    app.update();
+    app
+}
+
+#[test]
+fn default_level_build_stats_match_the_world() {
+    let mut app = build_default_level();
+    let world = app.world_mut();
+    let stats = *world.resource::<MapBuildStats>();
+
+    let entities = world
+        .query_filtered::<(), With<MapEntity>>()
+        .iter(world)
+        .count();
+    assert_eq!(stats.entities, entities);
+    assert_eq!(stats.meshes, world.resource::<Assets<Mesh>>().len());
+    assert_eq!(
+        stats.materials,
+        world.resource::<Assets<StandardMaterial>>().len()
+    );
+
+    // Only the rooms near the entry are streamed in. Their pieces are merged
+    // and same-sized surfaces share a mesh, so a jump here means batching
+    // has regressed.
+    assert_eq!((stats.entities, stats.meshes), (53, 22));
+    assert_eq!(stats.materials, 18);
+}
+
+fn load_every_room(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut builder: ResMut<MapBuilder>,
+    mut streaming: ResMut<LevelStreaming>,
+    levels: Res<Assets<Level>>,
+) {
+    let (_, level) = levels.iter().next().unwrap();
+    for (index, room) in level.rooms.iter().enumerate() {
+        streaming.load_room(
+            &mut commands,
+            &mut meshes,
+            &mut builder,
+            room,
+            RoomId(index),
+        );
+    }
+}
+
+// Materials are shared between rooms, so streaming in the rest of the level
+// must not create any.
+#[test]
+fn streaming_rooms_in_reuses_the_map_materials() {
+    let mut app = build_default_level();
+    let materials = app.world().resource::<Assets<StandardMaterial>>().len();
+
+    app.world_mut().run_system_once(load_every_room).unwrap();
+    let builder = app.world().resource::<MapBuilder>();
+    assert_eq!(builder.stats.materials, materials);
+    assert_eq!(
+        app.world().resource::<Assets<StandardMaterial>>().len(),
+        materials
+    );
+}
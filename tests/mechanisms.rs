@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use dark_city::entities::map::{
+    door::{DoorAction, DoorCommand},
+    level::parse_level,
+    mechanism::{
+        MechanismChanged, MechanismDef, MechanismInput, MechanismKind, Mechanisms,
+        evaluate_mechanisms,
+    },
+    utility::WallType,
+};
+
+fn mechanism_app(defs: &[MechanismDef]) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_event::<MechanismInput>()
+        .add_event::<MechanismChanged>()
+        .add_event::<DoorCommand>()
+        .insert_resource(Mechanisms::new(defs).unwrap())
+        .add_systems(Update, evaluate_mechanisms);
+    app
+}
+
+// Sends `inputs` for one frame and returns the door commands that came out.
+fn send(app: &mut App, inputs: &[(&str, bool)]) -> Vec<(String, WallType, DoorAction)> {
+    for &(name, active) in inputs {
+        app.world_mut().send_event(MechanismInput {
+            name: name.to_string(),
+            active,
+        });
+    }
+    app.update();
+    app.world_mut()
+        .resource_mut::<Events<DoorCommand>>()
+        .drain()
+        .map(|command| (command.room, command.side, command.action))
+        .collect()
+}
+
+fn door_actions(
+    room: &str,
+    side: WallType,
+    actions: &[DoorAction],
+) -> Vec<(String, WallType, DoorAction)> {
+    actions
+        .iter()
+        .map(|&action| (room.to_string(), side, action))
+        .collect()
+}
+
+#[test]
+fn door_output_follows_its_input() {
+    let def = |name: &str, kind| MechanismDef {
+        name: name.to_string(),
+        kind,
+    };
+    let mut app = mechanism_app(&[
+        def(
+            "Plate",
+            MechanismKind::PressurePlate {
+                offset: (0.0, 0.0, 0.0),
+                size: (1.0, 0.2, 1.0),
+            },
+        ),
+        def(
+            "Lever",
+            MechanismKind::Lever {
+                offset: (0.0, 0.0, 0.0),
+            },
+        ),
+        def(
+            "Both",
+            MechanismKind::And(vec!["Plate".to_string(), "Lever".to_string()]),
+        ),
+        def(
+            "Door",
+            MechanismKind::Door {
+                input: "Both".to_string(),
+                room: "Vault".to_string(),
+                side: WallType::NORTH,
+                door: None,
+            },
+        ),
+    ]);
+
+    assert_eq!(send(&mut app, &[("Plate", true)]), Vec::new());
+    assert_eq!(
+        send(&mut app, &[("Lever", true)]),
+        door_actions(
+            "Vault",
+            WallType::NORTH,
+            &[DoorAction::Unlock, DoorAction::Open]
+        )
+    );
+    assert_eq!(
+        send(&mut app, &[("Plate", false)]),
+        door_actions(
+            "Vault",
+            WallType::NORTH,
+            &[DoorAction::Close, DoorAction::Lock]
+        )
+    );
+}
+
+// The plate and lever of the first puzzle are too far apart to hold both, so
+// the door has to stay open once they have been used in order.
+#[test]
+fn default_level_first_puzzle_can_be_solved() {
+    let path = "assets/levels/default.level.ron";
+    let level = parse_level(path, &std::fs::read(path).unwrap()).unwrap();
+    let mut app = mechanism_app(&level.mechanisms);
+
+    assert_eq!(send(&mut app, &[("Puzzle 1 Plate", true)]), Vec::new());
+    assert_eq!(send(&mut app, &[("Puzzle 1 Plate", false)]), Vec::new());
+    assert_eq!(
+        send(&mut app, &[("Puzzle 1 Lever", true)]),
+        door_actions(
+            "Puzzle Room 1",
+            WallType::WEST,
+            &[DoorAction::Unlock, DoorAction::Open]
+        )
+    );
+    assert_eq!(send(&mut app, &[("Puzzle 1 Lever", false)]), Vec::new());
+}